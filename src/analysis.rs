@@ -0,0 +1,176 @@
+use crate::{Board, COLS, Evaluation, Solver};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Per-column evaluations of one position, as produced by `Solver::evaluate_moves`.
+#[derive(Debug, Clone, Copy)]
+pub struct PositionAnalysis {
+    pub key: u64, // `Board::key` of the analyzed position
+    pub depth: u8,
+    pub columns: [Option<Evaluation>; COLS],
+}
+
+impl PositionAnalysis {
+    /// Best column for the side to move, preferring the center on ties.
+    pub fn best_column(&self) -> Option<usize> {
+        let center = COLS as i32 / 2;
+        (0..COLS)
+            .filter_map(|col| self.columns[col].map(|eval| (col, eval)))
+            .max_by_key(|&(col, eval)| (eval.rank(), -(col as i32 - center).abs()))
+            .map(|(col, _)| col)
+    }
+
+    /// Value of the position for the side to move, i.e. the evaluation of its best move.
+    pub fn evaluation(&self) -> Option<Evaluation> {
+        self.best_column().and_then(|col| self.columns[col])
+    }
+}
+
+struct Job {
+    board: Board,
+    depth: u8,
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Job>,
+    running: Option<Arc<AtomicBool>>, // Stop flag of the job being searched
+    closed: bool,
+}
+
+/// Runs solver searches on a background thread so the GUI stays responsive.
+/// Requests are processed in order; results are collected with `poll`. A search that is no
+/// longer wanted can be stopped with `cancel`, so a deep one can't hold up the next.
+pub struct Analyzer {
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+    results: Receiver<PositionAnalysis>,
+}
+
+impl Analyzer {
+    /// Starts the worker thread. `on_result` is called from the worker after each result is
+    /// sent, e.g. to request a repaint.
+    pub fn spawn(on_result: impl Fn() + Send + 'static) -> Self {
+        let queue = Arc::new((Mutex::new(Queue::default()), Condvar::new()));
+        let (sender, results) = mpsc::channel();
+        let worker_queue = Arc::clone(&queue);
+        thread::spawn(move || run_worker(worker_queue, sender, on_result));
+        Self { queue, results }
+    }
+
+    /// Queues `board` for analysis unless the same position and depth is already waiting.
+    pub fn request(&self, board: &Board, depth: u8) {
+        let (lock, condvar) = &*self.queue;
+        let mut queue = lock.lock().unwrap();
+        if queue
+            .jobs
+            .iter()
+            .any(|job| job.board.key() == board.key() && job.depth == depth)
+        {
+            return;
+        }
+        queue.jobs.push_back(Job {
+            board: board.clone(),
+            depth,
+        });
+        condvar.notify_one();
+    }
+
+    /// Drops every queued request and stops the one being searched, whose result is then
+    /// never sent.
+    pub fn cancel(&self) {
+        let mut queue = self.queue.0.lock().unwrap();
+        queue.jobs.clear();
+        if let Some(stop) = queue.running.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the next finished analysis, if any.
+    pub fn poll(&self) -> Option<PositionAnalysis> {
+        self.results.try_recv().ok()
+    }
+}
+
+impl Drop for Analyzer {
+    fn drop(&mut self) {
+        self.cancel();
+        let (lock, condvar) = &*self.queue;
+        lock.lock().unwrap().closed = true;
+        condvar.notify_one();
+    }
+}
+
+fn run_worker(
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+    sender: Sender<PositionAnalysis>,
+    on_result: impl Fn(),
+) {
+    // One solver for the worker's lifetime so its memo carries over between positions
+    let mut solver = Solver::new();
+    let (lock, condvar) = &*queue;
+    loop {
+        let (job, stop) = {
+            let mut queue = lock.lock().unwrap();
+            loop {
+                if queue.closed {
+                    return;
+                }
+                if let Some(job) = queue.jobs.pop_front() {
+                    let stop = Arc::new(AtomicBool::new(false));
+                    queue.running = Some(Arc::clone(&stop));
+                    break (job, stop);
+                }
+                queue = condvar.wait(queue).unwrap();
+            }
+        };
+
+        solver.set_stop_flag(Some(Arc::clone(&stop)));
+        let columns = solver.evaluate_moves(&job.board, job.depth);
+        if stop.load(Ordering::Relaxed) {
+            continue;
+        }
+        let analysis = PositionAnalysis {
+            key: job.board.key(),
+            depth: job.depth,
+            columns,
+        };
+        if sender.send(analysis).is_err() {
+            return;
+        }
+        on_result();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn cancel_stops_the_running_search() {
+        let analyzer = Analyzer::spawn(|| {});
+        // Far too deep to finish, so only a cancel frees the worker
+        analyzer.request(&Board::new(), 30);
+        thread::sleep(Duration::from_millis(50));
+        analyzer.cancel();
+        let board = Board::from_moves("4453").unwrap();
+        analyzer.request(&board, 2);
+
+        let started = Instant::now();
+        let analysis = loop {
+            if let Some(analysis) = analyzer.poll() {
+                break analysis;
+            }
+            assert!(
+                started.elapsed() < Duration::from_secs(30),
+                "worker still busy"
+            );
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!((analysis.key, analysis.depth), (board.key(), 2));
+        assert!(analysis.columns.iter().all(Option::is_some));
+    }
+}
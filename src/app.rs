@@ -1,21 +1,189 @@
-use crate::{Board, COLS, Cell, Player, ROWS, Solver};
+use crate::record::unix_now;
+use crate::{
+    Analyzer, Board, COLS, Cell, EngineSettings, Evaluation, GameClock, GameRecord, GameResult,
+    Handicap, ImageOptions, MoveQuality, MoveResult, MoveReview, OPENINGS, Palette, PaletteChoice,
+    Player, PlayerKind, PositionAnalysis, PositionError, Puzzle, PuzzleBook, ROWS, Settings,
+    Solver, Statistics, Tally, Theme, TimeControl, export_png, generate_puzzles, review_game,
+};
 use eframe::egui;
-use log::{debug, info};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
 use std::time::{Duration, Instant};
 
 pub struct ConnectFourApp {
     board: Board,
     solver: Solver,
-    ai_player: Option<Player>,
-    thinking: bool,
+    red: PlayerKind,
+    yellow: PlayerKind,
+    ai_search: Option<AiSearch>, // Engine move being searched in the background
     game_mode: GameMode,
     ai_turns_to_win: Option<u8>, // AI turns remaining to a forced win
     ai_move_timer: Option<Instant>,
+    paused: bool,         // AI vs AI only: engines wait for Resume or Step
+    step_requested: bool, // AI vs AI only: play a single move while paused
+    setup_red_engine: EngineSettings,
+    setup_yellow_engine: EngineSettings,
+    setup_opening: String, // Move or grid string that games started from setup begin at
+    setup_handicap: Handicap,
+    handicap: Handicap,                 // Given to the human in the game on screen
+    notes: Vec<MoveNote>,               // One per ply in `board.moves()`
+    redo_stack: Vec<(usize, MoveNote)>, // Undone plies, most recently undone last
+    analysis_mode: bool,
+    analyzer: Option<Analyzer>,               // Spawned on first use
+    analyses: HashMap<u64, PositionAnalysis>, // Finished analyses by `Board::key`
+    analysis_requested: Option<(u64, u8)>,    // Key and depth of the last request
+    start_position: Board,                    // Where Reset Board returns to
+    editor_grid: [[Cell; COLS]; ROWS],
+    editor_side: Player,
+    drop_animation: Option<DropAnimation>,
+    keyboard_col: Option<usize>, // Column chosen with the arrow keys, previewed like a hover
+    settings: Settings,
+    show_settings: bool,
+    game_started_at: u64, // Seconds since the Unix epoch
+    show_game_file: bool,
+    game_file_path: String,
+    game_file_status: Option<Result<String, String>>, // Outcome of the last save, load or export
+    position_text: String, // Move or grid string typed or pasted to load a position
+    image_file_path: String,
+    image_options: ImageOptions, // Palette is taken from the settings on export
+    show_move_list: bool,
+    review: Option<(Vec<usize>, Vec<MoveReview>)>, // Plies reviewed and one review for each
+    reviewer: Option<Receiver<(Vec<usize>, Vec<MoveReview>)>>, // Review running in the background
+    hint_key: Option<u64>,                         // Position the human asked for a hint in
+    time_control: Option<TimeControl>,             // Chosen when the game started; None if untimed
+    clock: Option<GameClock>,                      // Timed games only
+    clock_at_swap: Option<GameClock>,              // Live clock as the last undo or redo left it
+    statistics: Statistics,
+    stats_recorded: bool, // The finished game on screen has been counted
+    puzzle_book: PuzzleBook,
+    puzzle: Option<Puzzle>,                      // Puzzle being played
+    puzzle_miner: Option<Receiver<Vec<Puzzle>>>, // Background search for new puzzles
+}
+
+/// On-disk save file: the game record plus the app mode needed to resume it. The record's
+/// fields are flattened, so the file also reads as a plain `GameRecord`.
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    #[serde(flatten)]
+    record: GameRecord,
+    #[serde(default)]
+    analysis_mode: bool,
+}
+
+/// An engine move searched on a worker thread. The solver travels with the search and comes
+/// back with the result, so its memo carries over to the next move. Dropping the search
+/// stops the worker, e.g. when the game is reset while the engine thinks.
+struct AiSearch {
+    key: u64, // `Board::key` of the position being searched
+    result: Receiver<(Solver, Option<MoveResult>)>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for AiSearch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Per-ply bookkeeping kept alongside the board's move list so undo/redo can restore it.
+#[derive(Debug, Clone, Default)]
+struct MoveNote {
+    by_engine: bool,
+    moves_to_win: Option<u8>, // Engine's forced-win distance in plies, counted from before its move
+    evaluation: Option<Evaluation>, // Engine's score for its own move
+    clock: Option<GameClock>, // Clock before the ply, or after it while it waits to be redone
+}
+
+/// A disc falling into place after a move; painted instead of the landed disc until it ends.
+#[derive(Debug, Clone, Copy)]
+struct DropAnimation {
+    row: usize,
+    col: usize,
+    started: Instant,
+}
+
+impl DropAnimation {
+    /// Fraction of the fall completed, from 0.0 to 1.0.
+    fn progress(&self) -> f32 {
+        // Deeper rows take longer to reach
+        let duration = Duration::from_millis(120 + 45 * self.row as u64);
+        (self.started.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+}
+
+/// Colors and markings for the board and discs, resolved from the settings.
+#[derive(Debug, Clone, Copy)]
+struct BoardStyle {
+    red: egui::Color32,
+    yellow: egui::Color32,
+    board: egui::Color32,
+    hole: (egui::Color32, egui::Stroke), // Empty cells
+    symbols: bool,
+}
+
+impl BoardStyle {
+    fn new(settings: &Settings) -> Self {
+        let Palette { red, yellow, board } = settings.palette();
+        let rgb = |[r, g, b]: [u8; 3]| egui::Color32::from_rgb(r, g, b);
+        let (board, hole) = match settings.theme {
+            Theme::Light => (
+                rgb(board),
+                (
+                    egui::Color32::WHITE,
+                    egui::Stroke::new(2.0, egui::Color32::LIGHT_GRAY),
+                ),
+            ),
+            // A dimmer board with holes that show the dark window behind it
+            Theme::Dark => (
+                shade(rgb(board), 0.75),
+                (
+                    egui::Color32::from_gray(30),
+                    egui::Stroke::new(2.0, egui::Color32::from_gray(60)),
+                ),
+            ),
+        };
+        Self {
+            red: rgb(red),
+            yellow: rgb(yellow),
+            board,
+            hole,
+            symbols: settings.disc_symbols,
+        }
+    }
+
+    fn player_color(&self, player: Player) -> egui::Color32 {
+        match player {
+            Player::Red => self.red,
+            Player::Yellow => self.yellow,
+        }
+    }
+
+    fn disc_colors(&self, cell: Cell) -> (egui::Color32, egui::Stroke) {
+        match cell {
+            Cell::Empty => self.hole,
+            Cell::Occupied(player) => {
+                let color = self.player_color(player);
+                (color, egui::Stroke::new(2.0, shade(color, 0.8)))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum GameMode {
     Setup,
+    Editor,
+    Puzzles, // Choosing a puzzle
     Playing,
 }
 
@@ -24,22 +192,133 @@ impl Default for ConnectFourApp {
         Self {
             board: Board::new(),
             solver: Solver::new(),
-            ai_player: None,
-            thinking: false,
+            red: PlayerKind::Human,
+            yellow: PlayerKind::Human,
+            ai_search: None,
             game_mode: GameMode::Setup,
             ai_turns_to_win: None,
             ai_move_timer: None,
+            paused: false,
+            step_requested: false,
+            setup_red_engine: EngineSettings::default(),
+            setup_yellow_engine: EngineSettings::default(),
+            setup_opening: String::new(),
+            setup_handicap: Handicap::None,
+            handicap: Handicap::None,
+            notes: Vec::new(),
+            redo_stack: Vec::new(),
+            analysis_mode: false,
+            analyzer: None,
+            analyses: HashMap::new(),
+            analysis_requested: None,
+            start_position: Board::new(),
+            editor_grid: [[Cell::Empty; COLS]; ROWS],
+            editor_side: Player::Red,
+            drop_animation: None,
+            keyboard_col: None,
+            settings: Settings::default(),
+            show_settings: false,
+            game_started_at: unix_now(),
+            show_game_file: false,
+            game_file_path: "connect_four_game.json".to_string(),
+            game_file_status: None,
+            position_text: String::new(),
+            image_file_path: "connect_four_board.png".to_string(),
+            image_options: ImageOptions::default(),
+            show_move_list: true,
+            review: None,
+            reviewer: None,
+            hint_key: None,
+            time_control: None,
+            clock: None,
+            clock_at_swap: None,
+            statistics: Statistics::default(),
+            stats_recorded: false,
+            puzzle_book: PuzzleBook::default(),
+            puzzle: None,
+            puzzle_miner: None,
+        }
+    }
+}
+
+impl ConnectFourApp {
+    /// Creates the app, restoring whatever `save` stored if eframe has any.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let settings = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Settings>(storage, Settings::STORAGE_KEY))
+            .unwrap_or_default();
+        let statistics = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, Statistics::STORAGE_KEY))
+            .unwrap_or_default();
+        let puzzle_book = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, PuzzleBook::STORAGE_KEY))
+            .unwrap_or_default();
+        Self {
+            // AI vs AI sides start out like the AI in the settings
+            setup_red_engine: settings.engine,
+            setup_yellow_engine: settings.engine,
+            settings,
+            statistics,
+            puzzle_book,
+            ..Self::default()
         }
     }
 }
 
 impl eframe::App for ConnectFourApp {
+    /// Keeps the settings, statistics and puzzle book between runs in eframe's storage, each
+    /// under its type's `STORAGE_KEY`.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, Settings::STORAGE_KEY, &self.settings);
+        eframe::set_value(storage, Statistics::STORAGE_KEY, &self.statistics);
+        eframe::set_value(storage, PuzzleBook::STORAGE_KEY, &self.puzzle_book);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        ctx.set_visuals(match self.settings.theme {
+            Theme::Light => egui::Visuals::light(),
+            Theme::Dark => egui::Visuals::dark(),
+        });
         let mut style = (*ctx.style()).clone();
         style.spacing.button_padding = egui::Vec2::new(12.0, 8.0);
         style.spacing.item_spacing = egui::Vec2::new(8.0, 8.0);
         ctx.set_style(style);
 
+        self.update_clock();
+        self.handle_keyboard(ctx);
+
+        egui::Area::new(egui::Id::new("settings_button"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if self.game_mode == GameMode::Playing
+                        && ui
+                            .selectable_label(self.show_move_list, "📜 Moves")
+                            .clicked()
+                    {
+                        self.show_move_list = !self.show_move_list;
+                    }
+                    if ui.button("💾 Save / Load").clicked() {
+                        self.show_game_file = !self.show_game_file;
+                    }
+                    if ui.button("⚙ Settings").clicked() {
+                        self.show_settings = !self.show_settings;
+                    }
+                });
+            });
+        self.show_settings_window(ctx);
+        self.show_game_file_window(ctx);
+
+        if self.game_mode == GameMode::Playing && self.show_move_list {
+            egui::SidePanel::right("move_list")
+                .resizable(false)
+                .exact_width(210.0)
+                .show(ctx, |ui| self.show_move_list(ui));
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.add_space(10.0);
@@ -52,113 +331,2161 @@ impl eframe::App for ConnectFourApp {
 
                 match self.game_mode {
                     GameMode::Setup => {
-                        self.show_setup_screen(ui);
+                        // The statistics below the setup options can outgrow small windows
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            self.show_setup_screen(ui);
+                        });
+                    }
+                    GameMode::Editor => {
+                        self.show_editor_screen(ui, ctx);
+                    }
+                    GameMode::Puzzles => {
+                        self.show_puzzles_screen(ui, ctx);
                     }
                     GameMode::Playing => {
                         self.show_game_screen(ui, ctx);
                     }
-                }
+                }
+            });
+        });
+
+        self.process_ai_move_with_delay(ctx);
+        self.record_statistics();
+        self.update_puzzles();
+        self.update_review();
+        self.update_analysis(ctx);
+        if self.drop_animation.is_some_and(|anim| anim.is_finished()) {
+            self.drop_animation = None;
+        }
+        // Only request repaint when needed to reduce CPU usage
+        if self.ai_move_timer.is_some() || self.drop_animation.is_some() || self.is_game_over() {
+            ctx.request_repaint();
+        } else if self
+            .clock
+            .as_ref()
+            .is_some_and(|clock| clock.running().is_some())
+        {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+}
+
+impl ConnectFourApp {
+    fn player_kind(&self, player: Player) -> PlayerKind {
+        match player {
+            Player::Red => self.red,
+            Player::Yellow => self.yellow,
+        }
+    }
+
+    fn engine_for(&self, player: Player) -> Option<EngineSettings> {
+        match self.player_kind(player) {
+            PlayerKind::Engine(settings) => Some(settings),
+            PlayerKind::Human => None,
+        }
+    }
+
+    /// Settings for the engine playing the side to move, with the handicap applied. `None`
+    /// when a human places this disc, including the AI's disc handed over by the handicap.
+    fn engine_to_move(&self) -> Option<EngineSettings> {
+        let engine = self.engine_for(self.board.current_player())?;
+        // The AI moves every other ply, so it has played half of the plies so far
+        self.handicap
+            .engine_for_move(engine, self.current_ply() / 2)
+    }
+
+    fn is_human(&self, player: Player) -> bool {
+        self.player_kind(player) == PlayerKind::Human
+    }
+
+    /// Whether an engine move is being searched.
+    fn thinking(&self) -> bool {
+        self.ai_search.is_some()
+    }
+
+    fn is_ai_vs_ai(&self) -> bool {
+        !self.is_human(Player::Red) && !self.is_human(Player::Yellow)
+    }
+
+    /// Side that lost on time, in a timed game that hasn't otherwise ended.
+    fn flagged(&self) -> Option<Player> {
+        if self.board.is_game_over() {
+            return None;
+        }
+        self.clock.as_ref()?.flagged()
+    }
+
+    fn is_game_over(&self) -> bool {
+        self.board.is_game_over() || self.flagged().is_some()
+    }
+
+    fn winner(&self) -> Option<Player> {
+        self.board
+            .winner()
+            .or_else(|| self.flagged().map(Player::opposite))
+    }
+
+    /// Runs the clock of the side to move, and stops it while the game is paused or over,
+    /// while the AI waits to continue from a reviewed ply and while its move waits out the
+    /// display delay. A human's clock keeps running after an undo or while reviewing, so
+    /// taking moves back doesn't buy thinking time.
+    fn update_clock(&mut self) {
+        let over = self.is_game_over();
+        let paused = self.is_ai_vs_ai() && self.paused && !self.step_requested;
+        let waiting = (self.is_reviewing() && self.engine_to_move().is_some())
+            || self.ai_move_timer.is_some();
+        let side_to_move = self.board.current_player();
+        let Some(clock) = &mut self.clock else {
+            return;
+        };
+        if over || paused || waiting || self.game_mode != GameMode::Playing {
+            if let Some(player) = clock.running() {
+                clock.stop();
+                if clock.remaining(player).is_zero() {
+                    info!("{} ran out of time", player.to_string());
+                }
+            }
+        } else {
+            clock.start(side_to_move);
+        }
+    }
+
+    /// Starts a game from the opening chosen on the setup screen.
+    fn start_game(&mut self, red: PlayerKind, yellow: PlayerKind) {
+        let start = self.setup_start().unwrap_or_default();
+        self.start_game_from(red, yellow, start);
+    }
+
+    /// Starting position for games begun from the setup screen: the empty board, or the
+    /// opening's move or grid string.
+    fn setup_start(&self) -> Result<Board, String> {
+        let board: Board = self
+            .setup_opening
+            .trim()
+            .parse()
+            .map_err(|err: PositionError| err.to_string())?;
+        if board.is_game_over() {
+            return Err("the opening already ends the game".to_string());
+        }
+        Ok(board)
+    }
+
+    fn start_game_from(&mut self, red: PlayerKind, yellow: PlayerKind, start: Board) {
+        self.red = red;
+        self.yellow = yellow;
+        self.game_mode = GameMode::Playing;
+        self.analysis_mode = false;
+        self.puzzle = None;
+        self.handicap = Handicap::None;
+        self.time_control = self.settings.time_control;
+        self.start_position = start;
+        self.reset_board();
+        self.paused = false;
+        self.step_requested = false;
+        self.game_started_at = unix_now();
+    }
+
+    /// Plays the game on screen with `control`, or untimed, from its start on.
+    fn set_time_control(&mut self, control: Option<TimeControl>) {
+        self.time_control = control;
+        self.clock = control.map(GameClock::new);
+    }
+
+    /// Record of the game up to the position on screen. Plies that have been undone and
+    /// could still be redone are not included.
+    fn game_record(&self) -> GameRecord {
+        let mut record = GameRecord::new(self.red, self.yellow);
+        record.started_at = self.game_started_at;
+        let start_moves = self.start_position.moves().len();
+        if self.start_position.key() != Board::new().key() {
+            record.start_position = Some(self.start_position.clone());
+        }
+        record.moves = self.board.moves()[start_moves..].to_vec();
+        record.handicap = self.handicap;
+        record.result = match self.flagged() {
+            Some(flagged) => GameResult::WinOnTime(flagged.opposite()),
+            None => GameResult::from_board(&self.board),
+        };
+        record.clock = self.clock.clone().map(|mut clock| {
+            clock.stop();
+            clock
+        });
+        if record.result != GameResult::InProgress {
+            record.finished_at = Some(unix_now());
+        }
+        record
+    }
+
+    fn save_game(&self, path: &str) -> Result<(), String> {
+        let saved = SavedGame {
+            record: self.game_record(),
+            analysis_mode: self.analysis_mode,
+        };
+        let json = serde_json::to_string_pretty(&saved).map_err(|err| err.to_string())?;
+        fs::write(path, json).map_err(|err| err.to_string())
+    }
+
+    /// Restores a saved game exactly as it was, ready to resume. AI vs AI games come back
+    /// paused.
+    fn load_game(&mut self, path: &str) -> Result<(), String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        // Parsing rebuilds the starting position from its grid and moves, so a start that
+        // couldn't arise in play is rejected here rather than trusted
+        let saved: SavedGame = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+        let record = saved.record;
+        if record.replay().is_none() {
+            return Err("the file contains an illegal move".to_string());
+        }
+
+        self.start_game_from(
+            record.red,
+            record.yellow,
+            record.start_position.clone().unwrap_or_default(),
+        );
+        self.analysis_mode = saved.analysis_mode;
+        self.handicap = record.handicap;
+        self.game_started_at = record.started_at;
+        self.time_control = record.clock.as_ref().map(GameClock::control);
+        self.clock = record.clock;
+        self.replay_moves(&record.moves);
+        self.paused = self.is_ai_vs_ai();
+        Ok(())
+    }
+
+    /// Plays already-validated moves onto the board without animation, as if they had just
+    /// been played by whoever controls each side.
+    fn replay_moves(&mut self, moves: &[usize]) {
+        for &col in moves {
+            let by_engine = self.engine_to_move().is_some();
+            self.board.make_move(col);
+            self.notes.push(MoveNote {
+                by_engine,
+                ..MoveNote::default()
+            });
+        }
+        self.refresh_forced_win();
+        // A game that was already over when loaded doesn't count again
+        self.stats_recorded = self.is_game_over();
+    }
+
+    /// Counts a finished Human vs AI game in the active profile's statistics, once.
+    fn record_statistics(&mut self) {
+        if self.stats_recorded
+            || self.analysis_mode
+            || self.puzzle.is_some()
+            || !self.is_game_over()
+        {
+            return;
+        }
+        self.stats_recorded = true;
+        let record = self.game_record();
+        if self.statistics.record_game(&self.settings.profile, &record) {
+            info!(
+                "Recorded {:?} for profile {}",
+                record.result, self.settings.profile
+            );
+        }
+    }
+
+    fn show_statistics(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.set_min_width(300.0);
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new("📊 Statistics vs AI")
+                        .size(16.0)
+                        .strong(),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Profile:");
+                ui.add(egui::TextEdit::singleline(&mut self.settings.profile).desired_width(120.0));
+                let others: Vec<String> = self
+                    .statistics
+                    .profiles
+                    .keys()
+                    .filter(|name| **name != self.settings.profile)
+                    .cloned()
+                    .collect();
+                if !others.is_empty() {
+                    egui::ComboBox::from_id_salt("profiles")
+                        .selected_text("Switch")
+                        .show_ui(ui, |ui| {
+                            for name in others {
+                                if ui.selectable_label(false, &name).clicked() {
+                                    self.settings.profile = name;
+                                }
+                            }
+                        });
+                }
+            });
+
+            let Some(stats) = self.statistics.profile(&self.settings.profile) else {
+                ui.label(
+                    egui::RichText::new("No finished games against the AI yet")
+                        .color(egui::Color32::GRAY),
+                );
+                return;
+            };
+            let summary = |tally: &Tally| {
+                format!(
+                    "{} games: {}W {}L {}D ({:.0}% won)",
+                    tally.games(),
+                    tally.wins,
+                    tally.losses,
+                    tally.draws,
+                    tally.win_rate().unwrap_or_default() * 100.0
+                )
+            };
+            egui::Grid::new("statistics_grid")
+                .num_columns(2)
+                .spacing([12.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Overall");
+                    ui.label(summary(&stats.overall));
+                    ui.end_row();
+                    ui.label("Moving first (Red)");
+                    ui.label(summary(&stats.moving_first));
+                    ui.end_row();
+                    ui.label("Moving second (Yellow)");
+                    ui.label(summary(&stats.moving_second));
+                    ui.end_row();
+                    for (engine, tally) in &stats.by_engine {
+                        ui.label(format!("AI {engine}"));
+                        ui.label(summary(tally));
+                        ui.end_row();
+                    }
+                    ui.label("Average length");
+                    ui.label(format!(
+                        "{:.1} plies",
+                        stats.overall.average_plies().unwrap_or_default()
+                    ));
+                    ui.end_row();
+                });
+            if ui.small_button("Reset profile").clicked() {
+                self.statistics.profiles.remove(&self.settings.profile);
+            }
+        });
+    }
+
+    /// Loads a pasted move or grid string, keeping the current players and mode when a game
+    /// is on screen. AI vs AI games come back paused.
+    fn load_position(&mut self, text: &str) -> Result<(), String> {
+        let board: Board = text
+            .trim()
+            .parse()
+            .map_err(|err: PositionError| err.to_string())?;
+        let (red, yellow, analysis_mode, time_control) = if self.game_mode == GameMode::Playing {
+            (self.red, self.yellow, self.analysis_mode, self.time_control)
+        } else {
+            (PlayerKind::Human, PlayerKind::Human, false, None)
+        };
+
+        if board.moves().is_empty() {
+            self.start_game_from(red, yellow, board);
+        } else {
+            // Keep the moves as history so they can be undone and stepped through
+            self.start_game_from(red, yellow, Board::new());
+            self.replay_moves(board.moves());
+        }
+        self.analysis_mode = analysis_mode;
+        self.set_time_control(time_control);
+        self.paused = self.is_ai_vs_ai();
+        Ok(())
+    }
+
+    fn show_game_file_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_game_file;
+        egui::Window::new("💾 Save / Load Game")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut self.game_file_path);
+                });
+                ui.horizontal(|ui| {
+                    let path = self.game_file_path.clone();
+                    if ui
+                        .add_enabled(
+                            self.game_mode == GameMode::Playing,
+                            egui::Button::new("💾 Save"),
+                        )
+                        .clicked()
+                    {
+                        let result = self.save_game(&path);
+                        match &result {
+                            Ok(()) => info!("Saved game to {path}"),
+                            Err(err) => warn!("Could not save game to {path}: {err}"),
+                        }
+                        self.game_file_status = Some(result.map(|()| format!("Saved to {path}")));
+                    }
+                    if ui.button("📂 Load").clicked() {
+                        let result = self.load_game(&path);
+                        match &result {
+                            Ok(()) => info!("Loaded game from {path}"),
+                            Err(err) => warn!("Could not load game from {path}: {err}"),
+                        }
+                        self.game_file_status = Some(result.map(|()| format!("Loaded {path}")));
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let playing = self.game_mode == GameMode::Playing;
+                    // A move string only describes games whose start position was itself
+                    // played from the empty board, such as the named openings
+                    let from_empty = self.start_position.has_move_history();
+                    if ui
+                        .add_enabled(playing && from_empty, egui::Button::new("📋 Copy moves"))
+                        .on_hover_text("Columns played, e.g. 4453")
+                        .on_disabled_hover_text(
+                            "Only for games started from the empty board or a move string",
+                        )
+                        .clicked()
+                    {
+                        ctx.copy_text(self.board.move_string());
+                        self.game_file_status = Some(Ok("Copied the moves".to_string()));
+                    }
+                    if ui
+                        .add_enabled(playing, egui::Button::new("📋 Copy grid"))
+                        .on_hover_text("Every cell from the top row down, and the side to move")
+                        .clicked()
+                    {
+                        ctx.copy_text(self.board.grid_string());
+                        self.game_file_status = Some(Ok("Copied the grid".to_string()));
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Position:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.position_text)
+                            .hint_text("Paste moves or a grid"),
+                    );
+                    if ui
+                        .add_enabled(
+                            !self.position_text.trim().is_empty(),
+                            egui::Button::new("📥 Load"),
+                        )
+                        .clicked()
+                    {
+                        let text = self.position_text.clone();
+                        let result = self.load_position(&text);
+                        match &result {
+                            Ok(()) => info!("Loaded position {}", text.trim()),
+                            Err(err) => warn!("Could not load position {}: {err}", text.trim()),
+                        }
+                        self.game_file_status =
+                            Some(result.map(|()| "Loaded the position".to_string()));
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Image:");
+                    ui.text_edit_singleline(&mut self.image_file_path);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.image_options.winning_line, "Winning line");
+                    ui.checkbox(&mut self.image_options.move_numbers, "Move numbers");
+                });
+                if ui
+                    .add_enabled(
+                        self.game_mode == GameMode::Playing,
+                        egui::Button::new("🖼 Export PNG"),
+                    )
+                    .clicked()
+                {
+                    let path = self.image_file_path.clone();
+                    let options = ImageOptions {
+                        palette: self.settings.palette(),
+                        ..self.image_options
+                    };
+                    let result =
+                        export_png(&self.board, &path, &options).map_err(|err| err.to_string());
+                    match &result {
+                        Ok(()) => info!("Exported board image to {path}"),
+                        Err(err) => warn!("Could not export board image to {path}: {err}"),
+                    }
+                    self.game_file_status = Some(result.map(|()| format!("Exported {path}")));
+                }
+                match &self.game_file_status {
+                    Some(Ok(message)) => {
+                        ui.label(egui::RichText::new(message).color(egui::Color32::DARK_GREEN));
+                    }
+                    Some(Err(err)) => {
+                        ui.label(
+                            egui::RichText::new(format!("⚠ {err}"))
+                                .color(egui::Color32::from_rgb(200, 60, 60)),
+                        );
+                    }
+                    None => {}
+                }
+            });
+        self.show_game_file = open;
+    }
+
+    fn reset_board(&mut self) {
+        self.board = self.start_position.clone();
+        self.clock = self.time_control.map(GameClock::new);
+        self.stats_recorded = false;
+        self.review = None;
+        self.reviewer = None;
+        self.notes.clear();
+        self.redo_stack.clear();
+        self.ai_turns_to_win = None;
+        self.ai_move_timer = None;
+        self.ai_search = None; // A search still running for the old board is stopped
+        self.solver = Solver::new(); // Start each game with an empty memo
+        self.drop_animation = None;
+    }
+
+    /// Plays a new move, discarding anything that could have been redone.
+    fn play_move(&mut self, col: usize, mut note: MoveNote) -> bool {
+        let Some(row) = self.board.landing_row(col) else {
+            return false;
+        };
+        let mover = self.board.current_player();
+        if !self.board.make_move(col) {
+            return false;
+        }
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+            note.clock = Some(clock.clone());
+            clock.press(mover);
+        }
+        self.drop_animation = Some(DropAnimation {
+            row,
+            col,
+            started: Instant::now(),
+        });
+        self.notes.push(note);
+        self.redo_stack.clear();
+        self.refresh_forced_win();
+        true
+    }
+
+    /// Re-derives the forced-win message from the last ply's note, so it stays correct after
+    /// undo and redo.
+    fn refresh_forced_win(&mut self) {
+        // After AI plays, remaining AI turns = floor(plies/2)
+        self.ai_turns_to_win = self
+            .notes
+            .last()
+            .filter(|note| note.by_engine)
+            .and_then(|note| note.moves_to_win)
+            .map(|plies| plies / 2);
+    }
+
+    /// Moving through the history is off while the AI thinks, and for the whole of a
+    /// puzzle so a failed attempt can't be taken back and replayed (Retry starts over).
+    fn can_navigate(&self) -> bool {
+        !self.thinking() && self.puzzle.is_none()
+    }
+
+    /// A loss on time is final, so undo is off once a side has flagged.
+    fn can_undo(&self) -> bool {
+        self.can_navigate() && self.current_ply() > 0 && self.flagged().is_none()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.can_navigate() && !self.redo_stack.is_empty()
+    }
+
+    /// Takes back one ply, or a full human + AI pair when playing against the AI so it is
+    /// the human's turn again. AI vs AI games are paused so the engines don't replay at once.
+    fn undo(&mut self) {
+        if !self.can_undo() {
+            return;
+        }
+        self.undo_ply();
+        if !self.is_ai_vs_ai() {
+            while self.engine_to_move().is_some() && self.current_ply() > 0 {
+                self.undo_ply();
+            }
+        } else {
+            self.paused = true;
+            self.step_requested = false;
+        }
+        self.ai_move_timer = None;
+        self.refresh_forced_win();
+    }
+
+    fn undo_ply(&mut self) {
+        if self.redo_stack.is_empty() {
+            // Nothing is being reviewed, so the live clock hasn't been swapped in yet
+            self.clock_at_swap = None;
+        }
+        if let Some(col) = self.board.undo_move() {
+            let mut note = self.notes.pop().unwrap_or_default();
+            self.swap_clock(&mut note);
+            self.redo_stack.push((col, note));
+        }
+    }
+
+    /// Exchanges the live clock with the one kept in `note`, turning the times back or
+    /// forward across its ply. Time used since the previous undo or redo is still charged,
+    /// so stepping back and forth doesn't win it back. Plies replayed from a file keep no
+    /// clock and leave it as is, and a clock that has flagged stays as it is so reviewing
+    /// can't reopen the game.
+    fn swap_clock(&mut self, note: &mut MoveNote) {
+        if self.flagged().is_some() {
+            return;
+        }
+        if let (Some(live), Some(kept)) = (&mut self.clock, &mut note.clock) {
+            live.stop();
+            if let Some(swapped_in) = &self.clock_at_swap {
+                for player in [Player::Red, Player::Yellow] {
+                    let used = swapped_in
+                        .remaining(player)
+                        .saturating_sub(live.remaining(player));
+                    kept.charge(player, used);
+                }
+            }
+            std::mem::swap(live, kept);
+            self.clock_at_swap = Some(live.clone());
+        }
+    }
+
+    /// Replays what `undo` took back: one ply, plus the AI's reply when playing the AI.
+    fn redo(&mut self) {
+        if !self.can_redo() {
+            return;
+        }
+        self.redo_ply();
+        if !self.is_ai_vs_ai() {
+            while self.engine_to_move().is_some()
+                && !self.board.is_game_over()
+                && !self.redo_stack.is_empty()
+            {
+                self.redo_ply();
+            }
+        }
+        self.ai_move_timer = None;
+        self.refresh_forced_win();
+    }
+
+    /// Whether the board shows an earlier point of the game, with later plies to redo.
+    fn is_reviewing(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Plies played since the starting position.
+    fn current_ply(&self) -> usize {
+        self.board.moves().len() - self.start_position.moves().len()
+    }
+
+    /// Moves the board to `ply` plies after the start, keeping the rest of the game redoable.
+    fn jump_to_ply(&mut self, ply: usize) {
+        if !self.can_navigate() {
+            return;
+        }
+        while self.current_ply() > ply {
+            self.undo_ply();
+        }
+        while self.current_ply() < ply && !self.redo_stack.is_empty() {
+            self.redo_ply();
+        }
+        if self.is_ai_vs_ai() {
+            self.paused = true;
+            self.step_requested = false;
+        }
+        self.drop_animation = None;
+        self.ai_move_timer = None;
+        self.refresh_forced_win();
+    }
+
+    fn show_move_list(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(40.0); // Room for the buttons in the top-right corner
+        ui.heading("📜 Moves");
+
+        let current = self.current_ply();
+        let total = current + self.redo_stack.len();
+        let idle = self.can_navigate();
+        ui.horizontal(|ui| {
+            let mut target = None;
+            if ui
+                .add_enabled(idle && current > 0, egui::Button::new("⏮"))
+                .on_hover_text("First")
+                .clicked()
+            {
+                target = Some(0);
+            }
+            if ui
+                .add_enabled(idle && current > 0, egui::Button::new("◀"))
+                .on_hover_text("Previous")
+                .clicked()
+            {
+                target = Some(current - 1);
+            }
+            if ui
+                .add_enabled(idle && current < total, egui::Button::new("▶"))
+                .on_hover_text("Next")
+                .clicked()
+            {
+                target = Some(current + 1);
+            }
+            if ui
+                .add_enabled(idle && current < total, egui::Button::new("⏭"))
+                .on_hover_text("Last")
+                .clicked()
+            {
+                target = Some(total);
+            }
+            if let Some(ply) = target {
+                self.jump_to_ply(ply);
+            }
+        });
+        ui.separator();
+
+        if self.evaluation_shown() {
+            self.show_evaluation_graph(ui);
+            ui.separator();
+        }
+
+        // Played plies followed by undone ones, each with the player and engine verdict
+        let rows: Vec<_> = self
+            .game_positions()
+            .into_iter()
+            .zip(self.ply_evaluations())
+            .zip(self.move_reviews())
+            .map(|(((_, col), (player, evaluation)), review)| (player, col, evaluation, review))
+            .collect();
+
+        let mut target = None;
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                if ui.selectable_label(current == 0, "Start").clicked() {
+                    target = Some(0);
+                }
+                for (index, (player, col, evaluation, review)) in rows.into_iter().enumerate() {
+                    let ply = index + 1;
+                    let emoji = match player {
+                        Player::Red => "🔴",
+                        Player::Yellow => "🟡",
+                    };
+                    let mut text = format!("{ply}. {emoji} column {}", col + 1);
+                    if let Some(review) = review {
+                        text.push_str(match review.quality {
+                            MoveQuality::Best => " ✔",
+                            MoveQuality::Inaccuracy => " ?!",
+                            MoveQuality::Blunder => " ??",
+                        });
+                    }
+                    if let Some(evaluation) = evaluation {
+                        text.push_str(&format!("  ({evaluation})"));
+                    }
+                    let mut label = egui::RichText::new(text);
+                    if ply > current {
+                        label = label.color(egui::Color32::GRAY);
+                    } else if let Some(review) = review {
+                        label = match review.quality {
+                            MoveQuality::Best => label,
+                            MoveQuality::Inaccuracy => {
+                                label.color(egui::Color32::from_rgb(200, 140, 40))
+                            }
+                            MoveQuality::Blunder => {
+                                label.color(egui::Color32::from_rgb(200, 60, 60))
+                            }
+                        };
+                    }
+                    let response = ui.selectable_label(ply == current, label);
+                    let response = match review {
+                        Some(review) if review.quality != MoveQuality::Best => response
+                            .on_hover_text(format!(
+                                "Played: {}. Better: column {} ({})",
+                                review.played,
+                                review.best_column + 1,
+                                review.best
+                            )),
+                        _ => response,
+                    };
+                    if response.clicked() {
+                        target = Some(ply);
+                    }
+                }
+            });
+        if let Some(ply) = target
+            && idle
+        {
+            self.jump_to_ply(ply);
+        }
+    }
+
+    /// Mover and evaluation of every ply, including undone ones. Background analysis of the
+    /// position before the move is preferred over the engine's own score.
+    fn ply_evaluations(&self) -> Vec<(Player, Option<Evaluation>)> {
+        let notes = self
+            .notes
+            .iter()
+            .chain(self.redo_stack.iter().rev().map(|(_, note)| note));
+        self.game_positions()
+            .into_iter()
+            .zip(notes)
+            .zip(self.move_reviews())
+            .map(|(((position, col), note), review)| {
+                let evaluation = review
+                    .map(|review| review.played)
+                    .or_else(|| {
+                        self.analyses
+                            .get(&position.key())
+                            .and_then(|analysis| analysis.columns[col])
+                    })
+                    .or(note.evaluation);
+                (position.current_player(), evaluation)
+            })
+            .collect()
+    }
+
+    /// Red's advantage in the current position, from -1.0 (Yellow wins) to 1.0 (Red wins),
+    /// with a description of where it came from. `None` until something has been evaluated.
+    fn current_advantage(&self) -> Option<(f32, String)> {
+        if self.is_game_over() {
+            return Some(match self.winner() {
+                Some(winner) => (
+                    red_advantage(winner, 1.0),
+                    format!("{} won", winner.to_string()),
+                ),
+                None => (0.0, "Draw".to_string()),
+            });
+        }
+        if let Some(eval) = self.current_analysis().and_then(|a| a.evaluation()) {
+            let player = self.board.current_player();
+            return Some((
+                red_advantage(player, eval.advantage()),
+                format!("{} to move: {eval}", player.to_string()),
+            ));
+        }
+        // Otherwise the last move's evaluation, from the side that played it
+        let last_ply = self.current_ply().checked_sub(1)?;
+        let (player, eval) = self.ply_evaluations()[last_ply];
+        let eval = eval?;
+        Some((
+            red_advantage(player, eval.advantage()),
+            format!("After {}'s move: {eval}", player.to_string()),
+        ))
+    }
+
+    /// Whether the evaluation bar and graph are drawn. A puzzle gets neither, as they would
+    /// give its answer away, and a game only gets them in analysis mode, with hints on, or
+    /// once it is over or reviewed.
+    fn evaluation_shown(&self) -> bool {
+        self.puzzle.is_none()
+            && (self.analysis_mode
+                || self.settings.show_hints
+                || self.is_game_over()
+                || self.review.is_some())
+    }
+
+    /// Vertical bar beside the board: the red share grows from the bottom as Red is favored.
+    /// The space is kept when the bar is hidden so the board doesn't shift.
+    fn paint_evaluation_bar(&self, ui: &mut egui::Ui, size: egui::Vec2) {
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+        if !self.evaluation_shown() {
+            return;
+        }
+        let painter = ui.painter();
+        let style = BoardStyle::new(&self.settings);
+        let (red, yellow) = (style.red, style.yellow);
+        let advantage = self.current_advantage();
+        let share = advantage
+            .as_ref()
+            .map_or(0.5, |(value, _)| (value + 1.0) / 2.0);
+
+        painter.rect_filled(rect, 4.0, yellow);
+        let split = rect.max.y - share * rect.height();
+        painter.rect_filled(
+            egui::Rect::from_min_max(egui::Pos2::new(rect.min.x, split), rect.max),
+            4.0,
+            red,
+        );
+        painter.hline(
+            rect.x_range(),
+            rect.center().y,
+            egui::Stroke::new(1.0, egui::Color32::from_gray(60)),
+        );
+        if advantage.is_none() {
+            painter.rect_filled(rect, 4.0, egui::Color32::from_white_alpha(140));
+        }
+        response.on_hover_text(advantage.map_or("Not evaluated yet".to_string(), |(_, text)| text));
+    }
+
+    /// Red's advantage after each ply, plotted over the move history. Click to jump there.
+    fn show_evaluation_graph(&mut self, ui: &mut egui::Ui) {
+        ui.label(egui::RichText::new("Evaluation").strong())
+            .on_hover_text("Above the middle favors Red, below favors Yellow");
+        let (rect, response) = ui.allocate_exact_size(
+            egui::Vec2::new(ui.available_width(), 80.0),
+            egui::Sense::click(),
+        );
+        let painter = ui.painter();
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+        painter.hline(
+            rect.x_range(),
+            rect.center().y,
+            egui::Stroke::new(1.0, egui::Color32::GRAY),
+        );
+
+        let style = BoardStyle::new(&self.settings);
+        let plies = self.ply_evaluations();
+        let total = plies.len().max(1) as f32;
+        let x_at = |ply: usize| rect.min.x + ply as f32 / total * rect.width();
+        let y_at = |advantage: f32| rect.center().y - advantage * (rect.height() / 2.0 - 4.0);
+
+        painter.vline(
+            x_at(self.current_ply()),
+            rect.y_range(),
+            egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 120, 200)),
+        );
+
+        let points: Vec<(egui::Pos2, f32)> = plies
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &(player, eval))| {
+                let advantage = red_advantage(player, eval?.advantage());
+                Some((egui::Pos2::new(x_at(index + 1), y_at(advantage)), advantage))
+            })
+            .collect();
+        painter.add(egui::Shape::line(
+            points.iter().map(|&(pos, _)| pos).collect(),
+            egui::Stroke::new(1.5, ui.visuals().text_color()),
+        ));
+        for &(pos, advantage) in &points {
+            let player = if advantage >= 0.0 {
+                Player::Red
+            } else {
+                Player::Yellow
+            };
+            painter.circle_filled(pos, 2.5, style.player_color(player));
+        }
+
+        if response.clicked()
+            && self.can_navigate()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let ply = ((pos.x - rect.min.x) / rect.width() * total).round() as usize;
+            self.jump_to_ply(ply.min(plies.len()));
+        }
+    }
+
+    fn redo_ply(&mut self) {
+        if let Some((col, mut note)) = self.redo_stack.pop() {
+            self.board.make_move(col);
+            self.swap_clock(&mut note);
+            self.notes.push(note);
+        }
+    }
+
+    /// Collects finished background analyses and queues the current position if it hasn't
+    /// been analyzed yet. Only analysis mode and hint requests analyze in the background, so
+    /// the engine's own search doesn't compete with it for time.
+    fn update_analysis(&mut self, ctx: &egui::Context) {
+        if let Some(analyzer) = &self.analyzer {
+            while let Some(analysis) = analyzer.poll() {
+                self.analyses.insert(analysis.key, analysis);
+            }
+        }
+
+        let key = self.board.key();
+        if self.game_mode != GameMode::Playing
+            || self.is_game_over()
+            || self.puzzle.is_some()
+            || !(self.analysis_mode || self.hint_key == Some(key))
+        {
+            return;
+        }
+        let depth = self.settings.engine.depth;
+        if self.analyses.get(&key).is_some_and(|a| a.depth == depth)
+            || self.analysis_requested == Some((key, depth))
+        {
+            return;
+        }
+
+        let board = self.board.clone();
+        let analyzer = self.analyzer(ctx);
+        // Only the latest position matters while the user is moving through a game
+        analyzer.cancel();
+        analyzer.request(&board, depth);
+        self.analysis_requested = Some((key, depth));
+    }
+
+    fn analyzer(&mut self, ctx: &egui::Context) -> &Analyzer {
+        self.analyzer.get_or_insert_with(|| {
+            let ctx = ctx.clone();
+            Analyzer::spawn(move || ctx.request_repaint())
+        })
+    }
+
+    /// Every ply of the game, including undone ones, with the position it was played from.
+    fn game_positions(&self) -> Vec<(Board, usize)> {
+        let start_moves = self.start_position.moves().len();
+        let played = self.board.moves()[start_moves..].iter().copied();
+        let undone = self.redo_stack.iter().rev().map(|&(col, _)| col);
+        let mut position = self.start_position.clone();
+        let mut positions = Vec::new();
+        for col in played.chain(undone) {
+            positions.push((position.clone(), col));
+            position.make_move(col);
+        }
+        positions
+    }
+
+    /// Reviews every ply of the game, including undone ones, with `review_game` on a worker
+    /// thread.
+    fn start_review(&mut self, ctx: &egui::Context) {
+        let depth = self.settings.engine.depth;
+        let start = self.start_position.clone();
+        let moves: Vec<usize> = self
+            .game_positions()
+            .into_iter()
+            .map(|(_, col)| col)
+            .collect();
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let reviews = review_game(&start, &moves, &mut Solver::new(), depth);
+            if sender.send((moves, reviews)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        self.reviewer = Some(receiver);
+    }
+
+    /// Collects the review once the worker thread has finished it.
+    fn update_review(&mut self) {
+        if let Some(reviewer) = &self.reviewer
+            && let Ok(review) = reviewer.try_recv()
+        {
+            self.review = Some(review);
+            self.reviewer = None;
+        }
+    }
+
+    /// Review of every ply of the game, where the finished review covers it. A ply keeps its
+    /// review only while the moves up to it are still the ones that were reviewed.
+    fn move_reviews(&self) -> Vec<Option<MoveReview>> {
+        let positions = self.game_positions();
+        let Some((moves, reviews)) = &self.review else {
+            return vec![None; positions.len()];
+        };
+        let mut same_game = true;
+        positions
+            .iter()
+            .enumerate()
+            .map(|(ply, (_, col))| {
+                same_game &= moves.get(ply) == Some(col);
+                reviews.get(ply).copied().filter(|_| same_game)
+            })
+            .collect()
+    }
+
+    fn show_review_summary(&self, ui: &mut egui::Ui) {
+        if self.review.is_none() {
+            ui.label(
+                egui::RichText::new("💭 Reviewing the game...")
+                    .size(13.0)
+                    .color(egui::Color32::GRAY),
+            );
+            return;
+        }
+        let reviews: Vec<MoveReview> = self.move_reviews().into_iter().flatten().collect();
+
+        for player in [Player::Red, Player::Yellow] {
+            let count = |quality| {
+                reviews
+                    .iter()
+                    .filter(|review| review.player == player && review.quality == quality)
+                    .count()
+            };
+            let (inaccuracies, blunders) =
+                (count(MoveQuality::Inaccuracy), count(MoveQuality::Blunder));
+            ui.label(
+                egui::RichText::new(format!(
+                    "{}: {} inaccurac{}, {} blunder{}",
+                    player.to_string(),
+                    inaccuracies,
+                    if inaccuracies == 1 { "y" } else { "ies" },
+                    blunders,
+                    if blunders == 1 { "" } else { "s" }
+                ))
+                .size(13.0),
+            );
+        }
+        ui.label(
+            egui::RichText::new("Hover a marked move in the move list to see the better column")
+                .size(12.0)
+                .color(egui::Color32::GRAY),
+        );
+    }
+
+    fn current_analysis(&self) -> Option<&PositionAnalysis> {
+        self.analyses.get(&self.board.key())
+    }
+
+    /// Whether the Hint button applies: hints are on in the settings, a human is playing, no
+    /// puzzle is being solved and the strip isn't already showing every column's evaluation.
+    fn hints_available(&self) -> bool {
+        self.settings.show_hints
+            && self.puzzle.is_none()
+            && !self.analysis_mode
+            && !self.is_game_over()
+            && (self.is_human(Player::Red) || self.is_human(Player::Yellow))
+    }
+
+    fn request_hint(&mut self) {
+        if self.hints_available() && self.human_to_move() {
+            self.hint_key = Some(self.board.key());
+        }
+    }
+
+    /// Analysis behind the hint for the current position, once the solver has finished it.
+    fn hint_analysis(&self) -> Option<&PositionAnalysis> {
+        if self.hint_key != Some(self.board.key())
+            || !self.human_to_move()
+            || !self.hints_available()
+        {
+            return None;
+        }
+        self.current_analysis()
+            .filter(|analysis| analysis.depth == self.settings.engine.depth)
+    }
+
+    fn show_hint_row(&mut self, ui: &mut egui::Ui) {
+        if let Some(analysis) = self.hint_analysis() {
+            let (Some(col), Some(eval)) = (analysis.best_column(), analysis.evaluation()) else {
+                return;
+            };
+            let outlook = match eval {
+                Evaluation::Win(_) => format!("you are winning ({eval})"),
+                Evaluation::Loss(_) => format!("you are losing ({eval})"),
+                Evaluation::Draw => "the game is drawn with best play".to_string(),
+                Evaluation::Heuristic(score) => {
+                    format!(
+                        "no forced result within {} plies ({score:+})",
+                        analysis.depth
+                    )
+                }
+            };
+            ui.label(
+                egui::RichText::new(format!("💡 Play column {}: {outlook}", col + 1))
+                    .size(15.0)
+                    .color(egui::Color32::from_rgb(40, 130, 60)),
+            );
+        } else if self.hint_key == Some(self.board.key()) && self.human_to_move() {
+            ui.label(
+                egui::RichText::new("💡 Looking for the best move...")
+                    .size(15.0)
+                    .color(egui::Color32::GRAY),
+            );
+        } else if ui
+            .add_enabled(self.human_to_move(), egui::Button::new("💡 Hint"))
+            .on_hover_text("Ask the solver for the best move (H)")
+            .clicked()
+        {
+            self.request_hint();
+        }
+    }
+
+    /// Paints each column's evaluation in the strip above the board.
+    fn paint_evaluation_strip(&self, painter: &egui::Painter, strip: egui::Rect, cell_size: f32) {
+        let Some(analysis) = self.current_analysis() else {
+            painter.text(
+                strip.center(),
+                egui::Align2::CENTER_CENTER,
+                "Analyzing...",
+                egui::FontId::proportional(13.0),
+                egui::Color32::GRAY,
+            );
+            return;
+        };
+
+        let best = analysis.best_column();
+        for col in 0..COLS {
+            let Some(eval) = analysis.columns[col] else {
+                continue;
+            };
+            let (text, color) = match eval {
+                Evaluation::Win(plies) => (
+                    format!("W{}", plies.div_ceil(2)),
+                    egui::Color32::from_rgb(40, 150, 60),
+                ),
+                Evaluation::Loss(plies) => (
+                    format!("L{}", plies / 2),
+                    egui::Color32::from_rgb(200, 60, 60),
+                ),
+                Evaluation::Draw => ("D".to_string(), egui::Color32::from_rgb(90, 90, 160)),
+                Evaluation::Heuristic(score) => {
+                    (format!("{score:+}"), egui::Color32::from_rgb(110, 110, 110))
+                }
+            };
+            let size = if best == Some(col) { 16.0 } else { 13.0 };
+            let center = egui::Pos2::new(
+                strip.min.x + (col as f32 + 0.5) * cell_size,
+                strip.center().y,
+            );
+            painter.text(
+                center,
+                egui::Align2::CENTER_CENTER,
+                text,
+                egui::FontId::proportional(size),
+                color,
+            );
+        }
+    }
+
+    /// Dims full columns and, for the hovered column, shows a ghost disc of the side to move
+    /// where a click would land.
+    fn paint_column_preview(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        cell_size: f32,
+        hovered_col: Option<usize>,
+    ) {
+        for col in 0..COLS {
+            let column_rect = egui::Rect::from_min_size(
+                rect.min + egui::Vec2::new(col as f32 * cell_size, 0.0),
+                egui::Vec2::new(cell_size, rect.height()),
+            );
+            match self.board.landing_row(col) {
+                None => {
+                    painter.rect_filled(
+                        column_rect,
+                        0.0,
+                        egui::Color32::from_rgba_unmultiplied(0, 0, 0, 90),
+                    );
+                }
+                Some(row) if hovered_col == Some(col) => {
+                    painter.rect_filled(
+                        column_rect,
+                        0.0,
+                        egui::Color32::from_rgba_unmultiplied(255, 255, 255, 40),
+                    );
+                    let color =
+                        BoardStyle::new(&self.settings).player_color(self.board.current_player());
+                    painter.circle_filled(
+                        cell_center(rect, cell_size, row, col),
+                        cell_size * 0.35,
+                        color.gamma_multiply(0.45),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings;
+        let before = self.settings.clone();
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 8.0])
+                    .show(ui, |ui| {
+                        engine_settings_rows(ui, &mut self.settings.engine);
+
+                        ui.label("AI move delay");
+                        ui.add(
+                            egui::Slider::new(&mut self.settings.ai_delay_ms, 0..=2000)
+                                .suffix(" ms"),
+                        );
+                        ui.end_row();
+
+                        let mut timed = self.settings.time_control.is_some();
+                        ui.checkbox(&mut timed, "Game clocks")
+                            .on_hover_text("Takes effect from the next game");
+                        let mut control = self.settings.time_control.unwrap_or_default();
+                        ui.add_enabled_ui(timed, |ui| {
+                            ui.vertical(|ui| {
+                                let mut minutes = control.initial_ms / 60_000;
+                                ui.add(egui::Slider::new(&mut minutes, 1..=30).suffix(" min each"));
+                                control.initial_ms = minutes * 60_000;
+                                let mut increment = control.increment_ms / 1000;
+                                ui.add(
+                                    egui::Slider::new(&mut increment, 0..=30).suffix(" s per move"),
+                                );
+                                control.increment_ms = increment * 1000;
+                            });
+                        });
+                        self.settings.time_control = timed.then_some(control);
+                        ui.end_row();
+
+                        ui.label("Theme");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.settings.theme, Theme::Light, "☀ Light");
+                            ui.radio_value(&mut self.settings.theme, Theme::Dark, "🌙 Dark");
+                        });
+                        ui.end_row();
+
+                        ui.label("Disc colors");
+                        egui::ComboBox::from_id_salt("palette")
+                            .selected_text(palette_name(self.settings.palette))
+                            .show_ui(ui, |ui| {
+                                for choice in [
+                                    PaletteChoice::Classic,
+                                    PaletteChoice::Colorblind,
+                                    PaletteChoice::HighContrast,
+                                    PaletteChoice::Custom,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut self.settings.palette,
+                                        choice,
+                                        palette_name(choice),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        if self.settings.palette == PaletteChoice::Custom {
+                            let custom = &mut self.settings.custom_palette;
+                            ui.label("Custom colors");
+                            ui.horizontal(|ui| {
+                                ui.color_edit_button_srgb(&mut custom.red)
+                                    .on_hover_text("Red's discs");
+                                ui.color_edit_button_srgb(&mut custom.yellow)
+                                    .on_hover_text("Yellow's discs");
+                                ui.color_edit_button_srgb(&mut custom.board)
+                                    .on_hover_text("Board");
+                            });
+                            ui.end_row();
+                        }
+
+                        ui.label("Disc symbols");
+                        ui.checkbox(&mut self.settings.disc_symbols, "X on red, O on yellow");
+                        ui.end_row();
+
+                        ui.label("Hints");
+                        ui.checkbox(&mut self.settings.show_hints, "Show hints")
+                            .on_hover_text("The Hint button and the AI's forced-win messages");
+                        ui.end_row();
+                    });
+
+                ui.add_space(5.0);
+                // The profile is who is playing rather than a preference, so it stays
+                if ui.button("Restore defaults").clicked() {
+                    self.settings = Settings {
+                        profile: std::mem::take(&mut self.settings.profile),
+                        ..Settings::default()
+                    };
+                }
+            });
+        self.show_settings = open;
+
+        // Apply engine changes to the AI of a Human vs AI game in progress, if it plays with
+        // the settings' engine; puzzle defenders and loaded engines keep their own
+        if self.settings.engine != before.engine && !self.is_ai_vs_ai() && self.puzzle.is_none() {
+            let (old, new) = (
+                PlayerKind::Engine(before.engine),
+                PlayerKind::Engine(self.settings.engine),
+            );
+            if self.red == old {
+                self.red = new;
+            }
+            if self.yellow == old {
+                self.yellow = new;
+            }
+        }
+    }
+
+    /// Returns to the setup screen.
+    fn new_game(&mut self) {
+        self.game_mode = GameMode::Setup;
+        self.puzzle = None;
+        self.start_position = Board::new();
+        self.reset_board();
+        self.red = PlayerKind::Human;
+        self.yellow = PlayerKind::Human;
+        self.analysis_mode = false;
+    }
+
+    fn human_to_move(&self) -> bool {
+        self.engine_to_move().is_none()
+            && !self.is_game_over()
+            && !self.thinking()
+            && self.ai_move_timer.is_none()
+    }
+
+    /// Drops a disc for the human to move, ignoring the request if it isn't their turn or
+    /// the column is full.
+    fn human_move(&mut self, col: usize) {
+        if !self.human_to_move() || col >= COLS || !self.board.is_valid_move(col) {
+            return;
+        }
+        debug!("Human plays column {}", col);
+        self.play_move(col, MoveNote::default());
+        if self.engine_to_move().is_some() {
+            self.ai_move_timer = Some(Instant::now());
+        }
+    }
+
+    /// Keyboard controls while a game is on screen: 1-7 drop a disc, Left/Right + Enter
+    /// choose a column, U or Ctrl+Z undo, Ctrl+Y or Ctrl+Shift+Z redo, H asks for a hint,
+    /// N starts a new game.
+    fn handle_keyboard(&mut self, ctx: &egui::Context) {
+        // Keys, undo shortcuts included, belong to a text field when one has focus
+        if self.game_mode != GameMode::Playing || ctx.wants_keyboard_input() {
+            return;
+        }
+        let redo_shortcut = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::Z,
+        );
+        let redo_shortcut_alt = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+        let undo_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+        // Check Ctrl+Shift+Z before Ctrl+Z, which would otherwise also match it
+        let (redo, undo) = ctx.input_mut(|i| {
+            let redo = i.consume_shortcut(&redo_shortcut) || i.consume_shortcut(&redo_shortcut_alt);
+            let undo = i.consume_shortcut(&undo_shortcut);
+            (redo, undo)
+        });
+        if redo {
+            self.redo();
+        } else if undo {
+            self.undo();
+        }
+
+        const COLUMN_KEYS: [egui::Key; COLS] = [
+            egui::Key::Num1,
+            egui::Key::Num2,
+            egui::Key::Num3,
+            egui::Key::Num4,
+            egui::Key::Num5,
+            egui::Key::Num6,
+            egui::Key::Num7,
+        ];
+        let none = egui::Modifiers::NONE;
+        let (column_key, left, right, enter, undo_key, hint, new_game) = ctx.input_mut(|i| {
+            (
+                COLUMN_KEYS.iter().position(|&key| i.consume_key(none, key)),
+                i.consume_key(none, egui::Key::ArrowLeft),
+                i.consume_key(none, egui::Key::ArrowRight),
+                i.consume_key(none, egui::Key::Enter),
+                i.consume_key(none, egui::Key::U),
+                i.consume_key(none, egui::Key::H),
+                i.consume_key(none, egui::Key::N),
+            )
+        });
+
+        if left || right {
+            let col = self.keyboard_col.unwrap_or(COLS / 2);
+            self.keyboard_col = Some(if left {
+                col.saturating_sub(1)
+            } else {
+                (col + 1).min(COLS - 1)
+            });
+        }
+        if let Some(col) = column_key {
+            self.keyboard_col = Some(col);
+            self.human_move(col);
+        } else if enter {
+            let col = *self.keyboard_col.get_or_insert(COLS / 2);
+            self.human_move(col);
+        }
+        if undo_key {
+            self.undo();
+        }
+        if hint {
+            self.request_hint();
+        }
+        if new_game {
+            self.new_game();
+        }
+    }
+
+    fn process_ai_move_with_delay(&mut self, ctx: &egui::Context) {
+        if self.game_mode != GameMode::Playing {
+            return;
+        }
+        if self.thinking() {
+            self.finish_ai_search();
+            return;
+        }
+        let Some(engine) = self.engine_to_move() else {
+            self.ai_move_timer = None;
+            return;
+        };
+        if self.is_game_over() {
+            self.ai_move_timer = None;
+            return;
+        }
+        if self.is_ai_vs_ai() && self.paused && !self.step_requested {
+            self.ai_move_timer = None;
+            return;
+        }
+        // While reviewing earlier moves against a human, the AI waits until the user lets it
+        // continue, which discards the plies that could be redone
+        if self.is_reviewing() && !self.is_ai_vs_ai() {
+            self.ai_move_timer = None;
+            return;
+        }
+
+        if self.ai_move_timer.is_none() {
+            self.ai_move_timer = Some(Instant::now());
+        }
+
+        // Let the previous disc finish dropping before the next one starts
+        let dropping = self.drop_animation.is_some_and(|anim| !anim.is_finished());
+        if let Some(timer) = self.ai_move_timer
+            && timer.elapsed() >= Duration::from_millis(self.settings.ai_delay_ms)
+            && !dropping
+        {
+            self.ai_move_timer = None;
+
+            // With a clock, the engine also stops when its share of the remaining time is used
+            let mut engine = engine;
+            if let Some(clock) = &self.clock {
+                let budget = clock.move_budget(self.board.current_player()).as_millis() as u64;
+                let limit = engine
+                    .time_limit_ms
+                    .map_or(budget, |limit| limit.min(budget));
+                engine.time_limit_ms = Some(limit.max(1));
+            }
+
+            self.start_ai_search(ctx, engine);
+        }
+    }
+
+    /// Searches the engine's move on a worker thread so the window stays responsive.
+    fn start_ai_search(&mut self, ctx: &egui::Context, engine: EngineSettings) {
+        let (sender, receiver) = mpsc::channel();
+        let mut solver = std::mem::take(&mut self.solver);
+        let stop = Arc::new(AtomicBool::new(false));
+        solver.set_stop_flag(Some(Arc::clone(&stop)));
+        let board = self.board.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = solver.search(&board, &engine);
+            if sender.send((solver, result)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        self.ai_search = Some(AiSearch {
+            key: self.board.key(),
+            result: receiver,
+            stop,
+        });
+    }
+
+    /// Plays the engine's move once its search has finished, unless the game has moved on
+    /// in the meantime (the AI lost on time, or the board was reset).
+    fn finish_ai_search(&mut self) {
+        let Some(search) = &self.ai_search else {
+            return;
+        };
+        let (solver, result) = match search.result.try_recv() {
+            Ok(finished) => finished,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.ai_search = None;
+                return;
+            }
+        };
+        let key = search.key;
+        self.ai_search = None;
+        self.solver = solver;
+        self.solver.set_stop_flag(None);
+        if key != self.board.key() || self.is_game_over() {
+            return;
+        }
+
+        if let Some(move_result) = result {
+            let turns_from_here = move_result.moves_to_win.map(|plies| plies.div_ceil(2)); // ceil(plies/2)
+            info!(
+                "AI ({}) selects column {}{}",
+                self.board.current_player().to_string(),
+                move_result.column,
+                turns_from_here
+                    .map(|t| format!(
+                        "; forced win in {} turn{}",
+                        t,
+                        if t == 1 { "" } else { "s" }
+                    ))
+                    .unwrap_or_default()
+            );
+            self.play_move(
+                move_result.column,
+                MoveNote {
+                    by_engine: true,
+                    moves_to_win: move_result.moves_to_win,
+                    evaluation: Some(move_result.evaluation),
+                    ..MoveNote::default()
+                },
+            );
+            // A step ends with its move, so the clock ran while the engine thought
+            self.step_requested = false;
+        }
+    }
+
+    fn show_setup_screen(&mut self, ui: &mut egui::Ui) {
+        self.show_opening_settings(ui);
+        let start_ok = self.setup_start().is_ok();
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.set_min_width(300.0);
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.label(
+                    egui::RichText::new("Choose who goes first:")
+                        .size(18.0)
+                        .strong(),
+                );
+                ui.add_space(15.0);
+
+                ui.add_enabled_ui(start_ok, |ui| {
+                    if ui
+                        .add_sized(
+                            [250.0, 40.0],
+                            egui::Button::new(
+                                egui::RichText::new("🔴 Human goes first (Red)").size(16.0),
+                            ),
+                        )
+                        .clicked()
+                    {
+                        self.start_game(
+                            PlayerKind::Human,
+                            PlayerKind::Engine(self.settings.engine),
+                        );
+                        self.handicap = self.setup_handicap;
+                    }
+
+                    ui.add_space(10.0);
+
+                    if ui
+                        .add_sized(
+                            [250.0, 40.0],
+                            egui::Button::new(
+                                egui::RichText::new("🤖 AI goes first (Red)").size(16.0),
+                            ),
+                        )
+                        .clicked()
+                    {
+                        self.start_game(
+                            PlayerKind::Engine(self.settings.engine),
+                            PlayerKind::Human,
+                        );
+                        self.handicap = self.setup_handicap;
+                        self.ai_move_timer = Some(Instant::now()); // Start timer for AI first move
+                    }
+
+                    ui.add_space(10.0);
+
+                    if ui
+                        .add_sized(
+                            [250.0, 40.0],
+                            egui::Button::new(egui::RichText::new("👥 Human vs Human").size(16.0)),
+                        )
+                        .clicked()
+                    {
+                        self.start_game(PlayerKind::Human, PlayerKind::Human);
+                    }
+
+                    ui.add_space(10.0);
+
+                    if ui
+                        .add_sized(
+                            [250.0, 40.0],
+                            egui::Button::new(egui::RichText::new("🔍 Analysis mode").size(16.0)),
+                        )
+                        .on_hover_text(
+                            "Play both sides and see the solver's evaluation of every column",
+                        )
+                        .clicked()
+                    {
+                        self.start_game(PlayerKind::Human, PlayerKind::Human);
+                        self.analysis_mode = true;
+                        self.set_time_control(None);
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                if ui
+                    .add_sized(
+                        [250.0, 40.0],
+                        egui::Button::new(egui::RichText::new("✏ Position editor").size(16.0)),
+                    )
+                    .on_hover_text("Set up a position, then play or analyze it")
+                    .clicked()
+                {
+                    self.game_mode = GameMode::Editor;
+                }
+
+                ui.add_space(10.0);
+
+                if ui
+                    .add_sized(
+                        [250.0, 40.0],
+                        egui::Button::new(egui::RichText::new("🧩 Puzzles").size(16.0)),
+                    )
+                    .on_hover_text("Find the forced win against a perfect defender")
+                    .clicked()
+                {
+                    self.game_mode = GameMode::Puzzles;
+                }
+
+                ui.add_space(10.0);
+            });
+        });
+
+        ui.add_space(15.0);
+
+        ui.group(|ui| {
+            ui.set_min_width(300.0);
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.label(egui::RichText::new("AI vs AI:").size(18.0).strong());
+                ui.add_space(10.0);
+
+                egui::Grid::new("ai_vs_ai_settings")
+                    .num_columns(2)
+                    .spacing([12.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new("🔴 Red").strong());
+                        ui.end_row();
+                        engine_settings_rows(ui, &mut self.setup_red_engine);
+                        ui.label(egui::RichText::new("🟡 Yellow").strong());
+                        ui.end_row();
+                        engine_settings_rows(ui, &mut self.setup_yellow_engine);
+                    });
+
+                ui.add_space(10.0);
+
+                ui.add_enabled_ui(start_ok, |ui| {
+                    if ui
+                        .add_sized(
+                            [250.0, 40.0],
+                            egui::Button::new(egui::RichText::new("🤖 Start AI vs AI").size(16.0)),
+                        )
+                        .clicked()
+                    {
+                        self.start_game(
+                            PlayerKind::Engine(self.setup_red_engine),
+                            PlayerKind::Engine(self.setup_yellow_engine),
+                        );
+                    }
+                });
+
+                ui.add_space(10.0);
+            });
+        });
+
+        ui.add_space(10.0);
+        self.show_statistics(ui);
+    }
+
+    /// Opening and handicap for the games started from the setup screen.
+    fn show_opening_settings(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.set_min_width(300.0);
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.label(egui::RichText::new("Start position:").size(18.0).strong());
+                ui.add_space(10.0);
+
+                egui::Grid::new("opening_settings")
+                    .num_columns(2)
+                    .spacing([12.0, 8.0])
+                    .show(ui, |ui| {
+                        let moves = self.setup_opening.trim();
+                        let selected = match OPENINGS.iter().find(|opening| opening.moves == moves)
+                        {
+                            Some(opening) => opening.name,
+                            None if moves.is_empty() => "Empty board",
+                            None => "Custom",
+                        };
+                        ui.label("Opening");
+                        egui::ComboBox::from_id_salt("opening")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut self.setup_opening,
+                                    String::new(),
+                                    "Empty board",
+                                );
+                                for opening in OPENINGS {
+                                    ui.selectable_value(
+                                        &mut self.setup_opening,
+                                        opening.moves.to_string(),
+                                        format!("{} ({})", opening.name, opening.moves),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Moves");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.setup_opening)
+                                .hint_text("Move or grid string, e.g. 4453")
+                                .desired_width(180.0),
+                        );
+                        ui.end_row();
+
+                        ui.label("Handicap");
+                        egui::ComboBox::from_id_salt("handicap")
+                            .selected_text(self.setup_handicap.name())
+                            .show_ui(ui, |ui| {
+                                for handicap in Handicap::ALL {
+                                    ui.selectable_value(
+                                        &mut self.setup_handicap,
+                                        handicap,
+                                        handicap.name(),
+                                    )
+                                    .on_hover_text(handicap.description());
+                                }
+                            })
+                            .response
+                            .on_hover_text(self.setup_handicap.description());
+                        ui.end_row();
+                    });
+
+                ui.add_space(5.0);
+                if let Err(err) = self.setup_start() {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {err}"))
+                            .color(egui::Color32::from_rgb(200, 60, 60)),
+                    );
+                } else if self.setup_handicap != Handicap::None {
+                    ui.label(
+                        egui::RichText::new(
+                            "Handicap games against the AI don't count in the statistics",
+                        )
+                        .color(egui::Color32::GRAY),
+                    );
+                }
+                ui.add_space(10.0);
+            });
+        });
+    }
+
+    /// Starts `puzzle` with the human on the side to move and a defender that searches deep
+    /// enough to delay the loss as long as possible.
+    fn start_puzzle(&mut self, puzzle: Puzzle) {
+        let board = match puzzle.board() {
+            Ok(board) => board,
+            Err(err) => {
+                warn!("Skipping puzzle {}: {err}", puzzle.moves);
+                return;
+            }
+        };
+        // Start from the bare position, so the setup moves can't be undone
+        let Ok(start) = Board::from_position(board.grid(), board.current_player()) else {
+            return;
+        };
+        let defender = PlayerKind::Engine(EngineSettings {
+            depth: puzzle.win_in * 2 + 2,
+            time_limit_ms: None,
+            ..self.settings.engine
+        });
+        let (red, yellow) = match start.current_player() {
+            Player::Red => (PlayerKind::Human, defender),
+            Player::Yellow => (defender, PlayerKind::Human),
+        };
+        self.start_game_from(red, yellow, start);
+        self.set_time_control(None);
+        self.puzzle = Some(puzzle);
+    }
+
+    /// Whether the puzzle on screen has been solved (`Some(true)`) or failed (`Some(false)`).
+    /// It is solved by winning within its number of moves, and failed once those moves are
+    /// used up without a win.
+    fn puzzle_outcome(&self) -> Option<bool> {
+        let puzzle = self.puzzle.as_ref()?;
+        let solver_side = self.start_position.current_player();
+        // The solver moves first, so it has made every other ply starting with the first
+        let moves_made = self.board.moves().len().div_ceil(2);
+        if self.board.winner() == Some(solver_side) && moves_made <= puzzle.win_in as usize {
+            Some(true)
+        } else if self.is_game_over() || moves_made >= puzzle.win_in as usize {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Marks solved puzzles and collects puzzles from a finished background search.
+    fn update_puzzles(&mut self) {
+        if self.puzzle_outcome() == Some(true)
+            && let Some(puzzle) = &self.puzzle
+            && !self.puzzle_book.is_solved(puzzle)
+        {
+            info!("Solved puzzle {}", puzzle.moves);
+            self.puzzle_book.mark_solved(puzzle);
+        }
+        if let Some(miner) = &self.puzzle_miner
+            && let Ok(puzzles) = miner.try_recv()
+        {
+            info!("Found {} new puzzles", puzzles.len());
+            self.puzzle_book.add_mined(puzzles);
+            self.puzzle_miner = None;
+        }
+    }
+
+    fn show_puzzle_status(&mut self, ui: &mut egui::Ui) {
+        let Some(puzzle) = self.puzzle.clone() else {
+            return;
+        };
+        let solver_side = self.start_position.current_player();
+        let moves_made = self.board.moves().len().div_ceil(2);
+        let (text, color) = match self.puzzle_outcome() {
+            Some(true) => (
+                "✅ Solved!".to_string(),
+                egui::Color32::from_rgb(40, 150, 60),
+            ),
+            Some(false) => (
+                "❌ Not solved: the defence held".to_string(),
+                egui::Color32::from_rgb(200, 60, 60),
+            ),
+            None => (
+                format!(
+                    "🧩 {} to move and win in {} ({} left)",
+                    solver_side.to_string(),
+                    puzzle.win_in,
+                    puzzle.win_in as usize - moves_made
+                ),
+                egui::Color32::GRAY,
+            ),
+        };
+        ui.label(egui::RichText::new(text).size(15.0).strong().color(color));
+
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - 230.0).max(0.0) / 2.0);
+            if ui.button("🔁 Retry").clicked() {
+                self.start_puzzle(puzzle.clone());
+            }
+            let puzzles = self.puzzle_book.puzzles();
+            let next = puzzles
+                .iter()
+                .position(|p| *p == puzzle)
+                .and_then(|index| puzzles.get(index + 1));
+            if ui
+                .add_enabled(next.is_some(), egui::Button::new("➡ Next puzzle"))
+                .clicked()
+                && let Some(next) = next
+            {
+                self.start_puzzle(next.clone());
+            }
+        });
+    }
+
+    fn show_puzzles_screen(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        ui.group(|ui| {
+            ui.set_min_width(300.0);
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.label(egui::RichText::new("🧩 Puzzles").size(18.0).strong());
+                ui.label(
+                    egui::RichText::new("Find the only winning line against a perfect defender")
+                        .size(13.0)
+                        .color(egui::Color32::GRAY),
+                );
+                ui.add_space(10.0);
+
+                let puzzles = self.puzzle_book.puzzles();
+                let mut chosen = None;
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        for (index, puzzle) in puzzles.iter().enumerate() {
+                            let side = if puzzle.moves.len() % 2 == 0 {
+                                "🔴 Red"
+                            } else {
+                                "🟡 Yellow"
+                            };
+                            let solved = if self.puzzle_book.is_solved(puzzle) {
+                                "  ✔"
+                            } else {
+                                ""
+                            };
+                            let text = format!(
+                                "#{}  {side} to move, win in {}{solved}",
+                                index + 1,
+                                puzzle.win_in
+                            );
+                            if ui
+                                .add_sized([250.0, 28.0], egui::Button::new(text))
+                                .clicked()
+                            {
+                                chosen = Some(puzzle.clone());
+                            }
+                        }
+                    });
+                if let Some(puzzle) = chosen {
+                    self.start_puzzle(puzzle);
+                }
+
+                ui.add_space(10.0);
+                let mining = self.puzzle_miner.is_some();
+                let label = if mining {
+                    "⛏ Searching..."
+                } else {
+                    "⛏ Find new puzzles"
+                };
+                if ui
+                    .add_enabled(!mining, egui::Button::new(label))
+                    .on_hover_text("Mine win-in-2 to win-in-4 puzzles from self-play")
+                    .clicked()
+                {
+                    let (sender, receiver) = mpsc::channel();
+                    let ctx = ctx.clone();
+                    let seed = unix_now() ^ puzzles.len() as u64;
+                    thread::spawn(move || {
+                        let puzzles = generate_puzzles(&mut Solver::new(), 3, 4, seed);
+                        if sender.send(puzzles).is_ok() {
+                            ctx.request_repaint();
+                        }
+                    });
+                    self.puzzle_miner = Some(receiver);
+                }
+                if ui.button("⬅ Back").clicked() {
+                    self.game_mode = GameMode::Setup;
+                }
+                ui.add_space(10.0);
+            });
+        });
+    }
+
+    fn show_editor_screen(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let validation = Board::from_position(self.editor_grid, self.editor_side);
+
+        ui.group(|ui| {
+            ui.set_min_width(400.0);
+            ui.vertical_centered(|ui| {
+                ui.add_space(5.0);
+                ui.label(egui::RichText::new("✏ Position Editor").size(18.0).strong());
+                ui.label(
+                    egui::RichText::new(
+                        "Click a cell to cycle empty → red → yellow; right-click to clear it",
+                    )
+                    .size(13.0)
+                    .color(egui::Color32::GRAY),
+                );
+                ui.horizontal(|ui| {
+                    ui.add_space((ui.available_width() - 330.0).max(0.0) / 2.0);
+                    ui.label("Side to move:");
+                    ui.radio_value(&mut self.editor_side, Player::Red, "🔴 Red");
+                    ui.radio_value(&mut self.editor_side, Player::Yellow, "🟡 Yellow");
+                    if ui.button("🧹 Clear").clicked() {
+                        self.editor_grid = [[Cell::Empty; COLS]; ROWS];
+                    }
+                });
+                ui.add_space(5.0);
             });
         });
 
-        self.process_ai_move_with_delay();
-        // Only request repaint when needed to reduce CPU usage
-        if self.thinking || self.ai_move_timer.is_some() || self.board.is_game_over() {
-            ctx.request_repaint();
-        }
-    }
-}
+        ui.add_space(15.0);
 
-impl ConnectFourApp {
-    fn process_ai_move_with_delay(&mut self) {
-        if let Some(ai_player) = self.ai_player {
-            if self.board.current_player() == ai_player
-                && !self.board.is_game_over()
-                && !self.thinking
-            {
-                if self.ai_move_timer.is_none() {
-                    self.ai_move_timer = Some(Instant::now());
-                }
+        ui.horizontal(|ui| {
+            let padding = 20.0;
+            let avail_w = ui.available_width();
+            let width_based = ((avail_w - padding) / COLS as f32).floor();
+            let viewport_h = ctx.available_rect().height();
+            let usable_h = (viewport_h - 190.0 - 160.0).max(120.0); // header + validation/buttons
+            let height_based = (usable_h / ROWS as f32).floor();
 
-                if let Some(timer) = self.ai_move_timer
-                    && timer.elapsed() >= Duration::from_millis(300)
-                {
-                    self.thinking = true;
-                    self.ai_move_timer = None;
-
-                    if let Some(move_result) = self.solver.find_best_move(&self.board, 9) {
-                        let turns_from_here =
-                            move_result.moves_to_win.map(|plies| plies.div_ceil(2)); // ceil(plies/2)
-                        info!(
-                            "AI selects column {}{}",
-                            move_result.column,
-                            turns_from_here
-                                .map(|t| format!(
-                                    "; forced win in {} turn{}",
-                                    t,
-                                    if t == 1 { "" } else { "s" }
-                                ))
-                                .unwrap_or_default()
-                        );
-                        self.board.make_move(move_result.column);
-                        // After AI plays, remaining AI turns = floor(plies/2)
-                        self.ai_turns_to_win = move_result.moves_to_win.map(|p| p / 2);
-                    }
+            let cell_size = width_based.min(height_based).max(36.0);
+            let board_width = COLS as f32 * cell_size;
+            let board_height = ROWS as f32 * cell_size;
+            ui.add_space(((avail_w - board_width) / 2.0).max(0.0));
+
+            let (rect, response) = ui.allocate_exact_size(
+                egui::Vec2::new(board_width, board_height),
+                egui::Sense::click(),
+            );
+            let style = BoardStyle::new(&self.settings);
+            paint_board(
+                ui.painter(),
+                rect,
+                cell_size,
+                &style,
+                &self.editor_grid,
+                None,
+            );
 
-                    self.thinking = false;
+            let clicked = response.clicked() || response.secondary_clicked();
+            if clicked && let Some(pos) = response.interact_pointer_pos() {
+                let relative_pos = pos - rect.min;
+                let col = (relative_pos.x / cell_size) as usize;
+                let row = (relative_pos.y / cell_size) as usize;
+                if col < COLS && row < ROWS {
+                    let cell = &mut self.editor_grid[row][col];
+                    *cell = if response.secondary_clicked() {
+                        Cell::Empty
+                    } else {
+                        match *cell {
+                            Cell::Empty => Cell::Occupied(Player::Red),
+                            Cell::Occupied(Player::Red) => Cell::Occupied(Player::Yellow),
+                            Cell::Occupied(Player::Yellow) => Cell::Empty,
+                        }
+                    };
                 }
-            } else {
-                self.ai_move_timer = None;
             }
-        }
-    }
+        });
 
-    fn show_setup_screen(&mut self, ui: &mut egui::Ui) {
-        ui.group(|ui| {
-            ui.set_min_width(300.0);
-            ui.vertical_centered(|ui| {
-                ui.add_space(10.0);
-                ui.label(
-                    egui::RichText::new("Choose who goes first:")
-                        .size(18.0)
-                        .strong(),
-                );
-                ui.add_space(15.0);
+        ui.add_space(10.0);
 
-                if ui
-                    .add_sized(
-                        [250.0, 40.0],
-                        egui::Button::new(
-                            egui::RichText::new("🔴 Human goes first (Red)").size(16.0),
-                        ),
-                    )
-                    .clicked()
-                {
-                    self.ai_player = Some(Player::Yellow);
-                    self.game_mode = GameMode::Playing;
-                    self.board.reset();
-                    self.ai_turns_to_win = None;
-                    self.ai_move_timer = None;
-                }
+        match &validation {
+            Ok(_) => ui.label(
+                egui::RichText::new("✔ Valid position")
+                    .size(14.0)
+                    .color(egui::Color32::DARK_GREEN),
+            ),
+            Err(err) => ui.label(
+                egui::RichText::new(format!("⚠ Invalid position: {err}"))
+                    .size(14.0)
+                    .color(egui::Color32::from_rgb(200, 60, 60)),
+            ),
+        };
 
-                ui.add_space(10.0);
+        ui.add_space(10.0);
 
-                if ui
-                    .add_sized(
-                        [250.0, 40.0],
-                        egui::Button::new(egui::RichText::new("🤖 AI goes first (Red)").size(16.0)),
-                    )
-                    .clicked()
-                {
-                    self.ai_player = Some(Player::Red);
-                    self.game_mode = GameMode::Playing;
-                    self.board.reset();
-                    self.ai_turns_to_win = None;
-                    self.ai_move_timer = Some(Instant::now()); // Start timer for AI first move
-                }
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - 620.0).max(0.0) / 2.0);
+            let button = |text: &str| {
+                egui::Button::new(egui::RichText::new(text).size(14.0))
+                    .min_size(egui::Vec2::new(140.0, 35.0))
+            };
+            let valid = validation.is_ok();
 
-                ui.add_space(10.0);
-            });
+            if ui
+                .add_enabled(valid, button("🤖 Play vs AI"))
+                .on_hover_text("You play the side to move")
+                .clicked()
+                && let Ok(board) = &validation
+            {
+                let ai = PlayerKind::Engine(self.settings.engine);
+                let (red, yellow) = match board.current_player() {
+                    Player::Red => (PlayerKind::Human, ai),
+                    Player::Yellow => (ai, PlayerKind::Human),
+                };
+                self.start_game_from(red, yellow, board.clone());
+            }
+
+            if ui.add_enabled(valid, button("👥 Human vs Human")).clicked()
+                && let Ok(board) = &validation
+            {
+                self.start_game_from(PlayerKind::Human, PlayerKind::Human, board.clone());
+            }
+
+            if ui.add_enabled(valid, button("🔍 Analyze")).clicked()
+                && let Ok(board) = &validation
+            {
+                self.start_game_from(PlayerKind::Human, PlayerKind::Human, board.clone());
+                self.analysis_mode = true;
+                self.set_time_control(None);
+            }
+
+            if ui.add(button("⬅ Back")).clicked() {
+                self.game_mode = GameMode::Setup;
+            }
         });
     }
 
@@ -167,8 +2494,12 @@ impl ConnectFourApp {
             ui.set_min_width(400.0);
             ui.vertical_centered(|ui| {
                 ui.add_space(5.0);
-                if self.board.is_game_over() {
-                    match self.board.winner() {
+                if let Some(clock) = &self.clock {
+                    ui.label(clock_text(clock, ui.visuals()));
+                    ui.add_space(5.0);
+                }
+                if self.is_game_over() {
+                    match self.winner() {
                         Some(winner) => {
                             let winner_text =
                                 format!("🎉 {} WINS! 🎉", winner.to_string().to_uppercase());
@@ -188,7 +2519,11 @@ impl ConnectFourApp {
                                 egui::RichText::new("🤝 IT'S A DRAW! 🤝")
                                     .size(24.0)
                                     .strong()
-                                    .color(egui::Color32::DARK_BLUE),
+                                    .color(if ui.visuals().dark_mode {
+                                        egui::Color32::LIGHT_BLUE
+                                    } else {
+                                        egui::Color32::DARK_BLUE
+                                    }),
                             );
                         }
                     }
@@ -198,14 +2533,31 @@ impl ConnectFourApp {
                         Player::Red => ("🔴", egui::Color32::from_rgb(180, 60, 60)),
                         Player::Yellow => ("🟡", egui::Color32::from_rgb(180, 140, 60)),
                     };
-                    let status_text = if let Some(ai_player) = self.ai_player {
-                        if current_player == ai_player {
-                            format!("{} AI's Turn ({})", emoji, current_player.to_string())
-                        } else {
+                    let status_text = match (self.red, self.yellow) {
+                        _ if self.analysis_mode => {
+                            format!("{} Analysis: {} to move", emoji, current_player.to_string())
+                        }
+                        (PlayerKind::Human, PlayerKind::Human) => {
+                            format!("{} Current Player: {}", emoji, current_player.to_string())
+                        }
+                        (PlayerKind::Engine(_), PlayerKind::Engine(_)) => {
+                            let depth = self.engine_for(current_player).map_or(0, |e| e.depth);
+                            format!(
+                                "{} {} AI's Turn (depth {})",
+                                emoji,
+                                current_player.to_string(),
+                                depth
+                            )
+                        }
+                        _ if self.is_human(current_player) => {
                             format!("{} Your Turn ({})", emoji, current_player.to_string())
                         }
-                    } else {
-                        format!("{} Current Player: {}", emoji, current_player.to_string())
+                        _ if self.engine_to_move().is_none() => format!(
+                            "{} Your extra move: place {}'s disc",
+                            emoji,
+                            current_player.to_string()
+                        ),
+                        _ => format!("{} AI's Turn ({})", emoji, current_player.to_string()),
                     };
 
                     ui.label(
@@ -218,27 +2570,58 @@ impl ConnectFourApp {
                     ui.add_space(5.0);
                     ui.vertical_centered(|ui| {
                         ui.set_min_height(20.0);
-                        if let Some(ai_player) = self.ai_player
-                            && self.board.current_player() == ai_player
-                            && (self.thinking || self.ai_move_timer.is_some())
+                        if !self.is_human(self.board.current_player())
+                            && (self.thinking() || self.ai_move_timer.is_some())
                         {
                             ui.label(
                                 egui::RichText::new("💭 AI is thinking...")
                                     .size(14.0)
                                     .color(egui::Color32::GRAY),
                             );
+                        } else if self.is_reviewing() {
+                            let current = self.current_ply();
+                            let total = current + self.redo_stack.len();
+                            let reviewing = format!("⏪ Reviewing move {current} of {total}");
+                            // Against the AI, nobody can move at a ply where the AI is to play
+                            // until the user lets it continue
+                            if !self.is_ai_vs_ai() && self.engine_to_move().is_some() {
+                                if ui
+                                    .button(format!("{reviewing}; ▶ let the AI continue from here"))
+                                    .on_hover_text("Discards the later moves")
+                                    .clicked()
+                                {
+                                    self.redo_stack.clear();
+                                }
+                            } else {
+                                let next = if self.is_ai_vs_ai() {
+                                    "resume or step to continue from here"
+                                } else {
+                                    "play a move to continue from here"
+                                };
+                                ui.label(
+                                    egui::RichText::new(format!("{reviewing}; {next}"))
+                                        .size(14.0)
+                                        .color(egui::Color32::GRAY),
+                                );
+                            }
                         }
                     });
 
-                    if let Some(ai_player) = self.ai_player
+                    if self.settings.show_hints
                         && let Some(turns_to_win) = self.ai_turns_to_win
                     {
-                        // Show AI forced-win info on the human's turn
-                        if self.board.current_player() != ai_player && turns_to_win > 0 {
+                        // Forced-win info for the engine that just moved
+                        let ai_player = self.board.current_player().opposite();
+                        if !self.is_human(ai_player) && turns_to_win > 0 {
                             ui.add_space(8.0);
                             let plural = if turns_to_win == 1 { "" } else { "s" };
+                            let who = if self.is_ai_vs_ai() {
+                                format!("{} AI", ai_player.to_string())
+                            } else {
+                                "AI".to_string()
+                            };
                             let win_text =
-                                format!("🎯 AI can force a win in {turns_to_win} turn{plural}");
+                                format!("🎯 {who} can force a win in {turns_to_win} turn{plural}");
                             ui.label(
                                 egui::RichText::new(win_text)
                                     .size(15.0)
@@ -246,6 +2629,15 @@ impl ConnectFourApp {
                             );
                         }
                     }
+
+                    if self.hints_available() {
+                        ui.add_space(5.0);
+                        self.show_hint_row(ui);
+                    }
+                }
+                if self.puzzle.is_some() {
+                    ui.add_space(5.0);
+                    self.show_puzzle_status(ui);
                 }
                 ui.add_space(5.0);
             });
@@ -257,85 +2649,146 @@ impl ConnectFourApp {
             // Responsive board: choose size from width, but also respect viewport height
             let padding = 20.0;
             let avail_w = ui.available_width();
-            let width_based = ((avail_w - padding) / COLS as f32).floor();
+            let bar_width = 16.0; // Evaluation bar, plus the same again as a gap to the board
+            let width_based = ((avail_w - padding - 4.0 * bar_width) / COLS as f32).floor();
 
             // Use overall viewport height to avoid tiny values due to prior widgets
             let viewport_h = ctx.available_rect().height();
-            let top_reserve = 170.0; // title + status + thinking area + spacing
-            let bottom_reserve = if self.board.is_game_over() {
-                180.0
+            let strip_height = if self.analysis_mode && !self.is_game_over() {
+                28.0
+            } else {
+                0.0
+            }; // per-column evaluations above the board
+            let hint_height = if self.hints_available() { 30.0 } else { 0.0 }; // Hint row
+            let clock_height = if self.clock.is_some() { 30.0 } else { 0.0 }; // Clock row
+            let puzzle_height = if self.puzzle.is_some() { 70.0 } else { 0.0 }; // Puzzle status
+            // Title, status, thinking area and spacing, plus the optional rows above
+            let top_reserve = 170.0 + strip_height + hint_height + clock_height + puzzle_height;
+            let bottom_reserve = if self.is_game_over() {
+                225.0
+            } else if self.is_ai_vs_ai() {
+                195.0
             } else {
-                100.0
-            }; // extra for game-over message
+                145.0
+            }; // extra for game-over message or AI vs AI controls
             let usable_h = (viewport_h - top_reserve - bottom_reserve).max(120.0);
             let height_based = (usable_h / ROWS as f32).floor();
 
             let cell_size = width_based.min(height_based).max(36.0);
             let board_width = COLS as f32 * cell_size;
             let board_height = ROWS as f32 * cell_size;
-            ui.add_space(((avail_w - board_width) / 2.0).max(0.0));
+            ui.add_space(((avail_w - board_width) / 2.0 - 2.0 * bar_width).max(0.0));
+            ui.vertical(|ui| {
+                ui.add_space(strip_height);
+                self.paint_evaluation_bar(ui, egui::Vec2::new(bar_width, board_height));
+            });
+            ui.add_space(bar_width);
 
-            let (rect, response) = ui.allocate_exact_size(
-                egui::Vec2::new(board_width, board_height),
+            let (full_rect, response) = ui.allocate_exact_size(
+                egui::Vec2::new(board_width, board_height + strip_height),
                 egui::Sense::click(),
             );
+            let rect = egui::Rect::from_min_max(
+                full_rect.min + egui::Vec2::new(0.0, strip_height),
+                full_rect.max,
+            );
             let painter = ui.painter();
 
-            painter.rect_filled(rect, 8.0, egui::Color32::from_rgb(41, 98, 255));
-
-            let inner_rect = rect.shrink(2.0);
-            painter.rect_filled(inner_rect, 6.0, egui::Color32::from_rgb(35, 85, 220));
-
-            for row in 0..ROWS {
-                for col in 0..COLS {
-                    let cell_rect = egui::Rect::from_min_size(
-                        rect.min + egui::Vec2::new(col as f32 * cell_size, row as f32 * cell_size),
-                        egui::Vec2::splat(cell_size),
-                    );
+            if strip_height > 0.0 {
+                let strip = egui::Rect::from_min_max(
+                    full_rect.min,
+                    egui::Pos2::new(full_rect.max.x, rect.min.y),
+                );
+                self.paint_evaluation_strip(painter, strip, cell_size);
+            }
 
-                    let (color, stroke) = match self.board.get_cell(row, col) {
-                        Cell::Empty => (
-                            egui::Color32::WHITE,
-                            egui::Stroke::new(2.0, egui::Color32::LIGHT_GRAY),
-                        ),
-                        Cell::Occupied(Player::Red) => (
-                            egui::Color32::from_rgb(220, 50, 50),
-                            egui::Stroke::new(2.0, egui::Color32::from_rgb(180, 40, 40)),
-                        ),
-                        Cell::Occupied(Player::Yellow) => (
-                            egui::Color32::from_rgb(255, 215, 50),
-                            egui::Stroke::new(2.0, egui::Color32::from_rgb(200, 160, 40)),
-                        ),
-                    };
+            paint_board(
+                painter,
+                rect,
+                cell_size,
+                &BoardStyle::new(&self.settings),
+                &self.board.grid(),
+                self.drop_animation.as_ref(),
+            );
+            // Mark the winning line once the last disc has landed
+            if self.drop_animation.is_none() {
+                paint_winning_line(painter, rect, cell_size, &self.board);
+            }
+            if let Some(col) = self.hint_analysis().and_then(|a| a.best_column()) {
+                let column_rect = egui::Rect::from_min_size(
+                    rect.min + egui::Vec2::new(col as f32 * cell_size, 0.0),
+                    egui::Vec2::new(cell_size, rect.height()),
+                );
+                painter.rect_stroke(
+                    column_rect.shrink(2.0),
+                    6.0,
+                    egui::Stroke::new(3.0, egui::Color32::from_rgb(60, 200, 90)),
+                    egui::StrokeKind::Inside,
+                );
+            }
 
-                    let radius = cell_size * 0.35;
-                    painter.circle_filled(cell_rect.center(), radius, color);
-                    painter.circle_stroke(cell_rect.center(), radius, stroke);
+            if self.human_to_move() {
+                let hovered_col = response
+                    .hover_pos()
+                    .map(|pos| ((pos.x - rect.min.x) / cell_size) as usize)
+                    .filter(|&col| col < COLS);
+                if hovered_col.is_some() && ui.input(|i| i.pointer.is_moving()) {
+                    self.keyboard_col = None; // The mouse takes over from the keyboard
+                }
+                self.paint_column_preview(
+                    painter,
+                    rect,
+                    cell_size,
+                    self.keyboard_col.or(hovered_col),
+                );
+                if hovered_col.is_some_and(|col| self.board.is_valid_move(col)) {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
                 }
             }
 
             if response.clicked()
-                && let Some(ai_player) = self.ai_player
-                && self.board.current_player() != ai_player
-                && !self.board.is_game_over()
-                && !self.thinking
-                && self.ai_move_timer.is_none()
                 && let Some(pos) = response.interact_pointer_pos()
             {
                 let relative_pos = pos - rect.min;
                 let col = (relative_pos.x / cell_size) as usize;
-
-                if col < COLS && self.board.is_valid_move(col) {
-                    debug!("Human plays column {}", col);
-                    self.board.make_move(col);
-                    self.ai_turns_to_win = None;
-                    self.ai_move_timer = Some(Instant::now());
-                }
+                self.human_move(col);
             }
         });
 
         ui.add_space(20.0);
 
+        ui.horizontal(|ui| {
+            ui.add_space((ui.available_width() - 300.0) / 2.0); // Center the buttons
+
+            if ui
+                .add_enabled(
+                    self.can_undo(),
+                    egui::Button::new(egui::RichText::new("↶ Undo").size(14.0))
+                        .min_size(egui::Vec2::new(140.0, 35.0)),
+                )
+                .on_hover_text("Ctrl+Z or U")
+                .clicked()
+            {
+                self.undo();
+            }
+
+            ui.add_space(20.0);
+
+            if ui
+                .add_enabled(
+                    self.can_redo(),
+                    egui::Button::new(egui::RichText::new("↷ Redo").size(14.0))
+                        .min_size(egui::Vec2::new(140.0, 35.0)),
+                )
+                .on_hover_text("Ctrl+Y or Ctrl+Shift+Z")
+                .clicked()
+            {
+                self.redo();
+            }
+        });
+
+        ui.add_space(10.0);
+
         ui.horizontal(|ui| {
             ui.add_space((ui.available_width() - 300.0) / 2.0); // Center the buttons
 
@@ -344,13 +2797,10 @@ impl ConnectFourApp {
                     [140.0, 35.0],
                     egui::Button::new(egui::RichText::new("🆕 New Game").size(14.0)),
                 )
+                .on_hover_text("N")
                 .clicked()
             {
-                self.game_mode = GameMode::Setup;
-                self.board.reset();
-                self.ai_player = None;
-                self.ai_turns_to_win = None;
-                self.ai_move_timer = None;
+                self.new_game();
             }
 
             ui.add_space(20.0);
@@ -362,18 +2812,52 @@ impl ConnectFourApp {
                 )
                 .clicked()
             {
-                self.board.reset();
-                self.ai_turns_to_win = None;
-                self.ai_move_timer = None;
+                self.reset_board();
             }
         });
 
-        if self.board.is_game_over() {
+        if self.is_ai_vs_ai() && !self.is_game_over() {
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 300.0) / 2.0);
+
+                let pause_label = if self.paused {
+                    "▶ Resume"
+                } else {
+                    "⏸ Pause"
+                };
+                if ui
+                    .add_sized(
+                        [140.0, 35.0],
+                        egui::Button::new(egui::RichText::new(pause_label).size(14.0)),
+                    )
+                    .clicked()
+                {
+                    self.paused = !self.paused;
+                    self.step_requested = false;
+                }
+
+                ui.add_space(20.0);
+
+                if ui
+                    .add_enabled(
+                        self.paused && !self.step_requested,
+                        egui::Button::new(egui::RichText::new("⏭ Step").size(14.0))
+                            .min_size(egui::Vec2::new(140.0, 35.0)),
+                    )
+                    .clicked()
+                {
+                    self.step_requested = true;
+                }
+            });
+        }
+
+        if self.is_game_over() {
             self.show_game_over_overlay(ui, ctx);
         }
     }
 
-    fn show_game_over_overlay(&self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+    fn show_game_over_overlay(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.add_space(15.0);
 
         ui.group(|ui| {
@@ -381,20 +2865,32 @@ impl ConnectFourApp {
             ui.vertical_centered(|ui| {
                 ui.add_space(10.0);
 
-                match self.board.winner() {
+                match self.winner() {
                     Some(winner) => {
-                        let message = format!("Game Over - {} is the winner!", winner.to_string());
+                        let message = if self.flagged().is_some() {
+                            format!("Game Over - {} wins on time!", winner.to_string())
+                        } else {
+                            format!("Game Over - {} is the winner!", winner.to_string())
+                        };
                         ui.label(
                             egui::RichText::new(message)
                                 .size(16.0)
-                                .color(egui::Color32::DARK_GREEN),
+                                .color(if ui.visuals().dark_mode {
+                                    egui::Color32::LIGHT_GREEN
+                                } else {
+                                    egui::Color32::DARK_GREEN
+                                }),
                         );
                     }
                     None => {
                         ui.label(
                             egui::RichText::new("Game Over - It's a draw!")
                                 .size(16.0)
-                                .color(egui::Color32::DARK_BLUE),
+                                .color(if ui.visuals().dark_mode {
+                                    egui::Color32::LIGHT_BLUE
+                                } else {
+                                    egui::Color32::DARK_BLUE
+                                }),
                         );
                     }
                 }
@@ -404,8 +2900,438 @@ impl ConnectFourApp {
                         .size(12.0)
                         .color(egui::Color32::GRAY),
                 );
+
+                ui.add_space(5.0);
+                if self.review.is_none() && self.reviewer.is_none() {
+                    if ui
+                        .button("🔍 Review game")
+                        .on_hover_text("Re-solve every position and mark best moves, inaccuracies and blunders")
+                        .clicked()
+                    {
+                        self.start_review(ctx);
+                    }
+                } else {
+                    self.show_review_summary(ui);
+                }
                 ui.add_space(10.0);
             });
         });
     }
 }
+
+/// Paints the blue frame and every disc of `grid` into `rect`. A disc that is still
+/// dropping is drawn part-way down its column instead of in its cell.
+fn paint_board(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    cell_size: f32,
+    style: &BoardStyle,
+    grid: &[[Cell; COLS]; ROWS],
+    drop: Option<&DropAnimation>,
+) {
+    painter.rect_filled(rect, 8.0, style.board);
+
+    let inner_rect = rect.shrink(2.0);
+    painter.rect_filled(inner_rect, 6.0, shade(style.board, 0.86));
+
+    let radius = cell_size * 0.35;
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            let cell = match drop {
+                Some(anim) if anim.row == row && anim.col == col => Cell::Empty,
+                _ => cell,
+            };
+            let center = cell_center(rect, cell_size, row, col);
+            paint_disc(painter, center, radius, style, cell);
+        }
+    }
+
+    if let Some(anim) = drop {
+        let target = cell_center(rect, cell_size, anim.row, anim.col);
+        // Start just above the board and accelerate like a falling disc
+        let start_y = rect.min.y - cell_size * 0.5;
+        let t = anim.progress();
+        let center = egui::Pos2::new(target.x, start_y + (target.y - start_y) * t * t);
+        let clipped = painter.with_clip_rect(rect);
+        paint_disc(&clipped, center, radius, style, grid[anim.row][anim.col]);
+    }
+}
+
+/// Paints one cell's disc or hole, with the player's symbol when enabled: a cross on red
+/// discs and a ring on yellow ones.
+fn paint_disc(
+    painter: &egui::Painter,
+    center: egui::Pos2,
+    radius: f32,
+    style: &BoardStyle,
+    cell: Cell,
+) {
+    let (color, stroke) = style.disc_colors(cell);
+    painter.circle_filled(center, radius, color);
+    painter.circle_stroke(center, radius, stroke);
+
+    let Cell::Occupied(player) = cell else {
+        return;
+    };
+    if !style.symbols {
+        return;
+    }
+    let mark = egui::Stroke::new(radius * 0.18, shade(color, 0.5));
+    match player {
+        Player::Red => {
+            let arm = radius * 0.4;
+            for (dx, dy) in [(arm, arm), (arm, -arm)] {
+                painter.line_segment(
+                    [
+                        center - egui::Vec2::new(dx, dy),
+                        center + egui::Vec2::new(dx, dy),
+                    ],
+                    mark,
+                );
+            }
+        }
+        Player::Yellow => {
+            painter.circle_stroke(center, radius * 0.45, mark);
+        }
+    }
+}
+
+/// Darkens `color` by `factor`, from 0.0 (black) to 1.0 (unchanged).
+fn shade(color: egui::Color32, factor: f32) -> egui::Color32 {
+    let scale = |channel: u8| (channel as f32 * factor) as u8;
+    egui::Color32::from_rgb(scale(color.r()), scale(color.g()), scale(color.b()))
+}
+
+/// Both clocks on one line, the running one in bold and either in red when nearly out.
+fn clock_text(clock: &GameClock, visuals: &egui::Visuals) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    for (index, (player, emoji)) in [(Player::Red, "🔴"), (Player::Yellow, "🟡")]
+        .into_iter()
+        .enumerate()
+    {
+        if index > 0 {
+            job.append("      ", 0.0, egui::TextFormat::default());
+        }
+        let remaining = clock.remaining(player);
+        let color = if remaining < Duration::from_secs(10) {
+            egui::Color32::from_rgb(220, 50, 50)
+        } else if clock.running() == Some(player) {
+            visuals.strong_text_color()
+        } else {
+            visuals.weak_text_color()
+        };
+        let size = if clock.running() == Some(player) {
+            20.0
+        } else {
+            17.0
+        };
+        job.append(
+            &format!("{emoji} {}", format_clock(remaining)),
+            0.0,
+            egui::TextFormat::simple(egui::FontId::monospace(size), color),
+        );
+    }
+    job
+}
+
+/// m:ss, with tenths of a second under ten seconds.
+fn format_clock(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    if seconds < 10 {
+        format!("0:{:02}.{}", seconds, remaining.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Grid rows for an engine's search depth, time limit per move and threads.
+fn engine_settings_rows(ui: &mut egui::Ui, engine: &mut EngineSettings) {
+    let depth_label = if engine.time_limit_ms.is_some() {
+        "Max search depth"
+    } else {
+        "Search depth"
+    };
+    ui.label(depth_label);
+    ui.add(egui::Slider::new(&mut engine.depth, 1..=20));
+    ui.end_row();
+
+    let mut limit_time = engine.time_limit_ms.is_some();
+    ui.checkbox(&mut limit_time, "Time limit per move");
+    let mut millis = engine.time_limit_ms.unwrap_or(1000);
+    ui.add_enabled(
+        limit_time,
+        egui::Slider::new(&mut millis, 100..=10_000).suffix(" ms"),
+    );
+    engine.time_limit_ms = limit_time.then_some(millis);
+    ui.end_row();
+
+    let max_threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .max(engine.threads);
+    ui.label("Search threads");
+    ui.add(egui::Slider::new(&mut engine.threads, 1..=max_threads));
+    ui.end_row();
+}
+
+fn palette_name(choice: PaletteChoice) -> &'static str {
+    match choice {
+        PaletteChoice::Classic => "Classic",
+        PaletteChoice::Colorblind => "Colorblind-safe",
+        PaletteChoice::HighContrast => "High contrast",
+        PaletteChoice::Custom => "Custom",
+    }
+}
+
+/// Flips an advantage for `player` into Red's point of view.
+fn red_advantage(player: Player, advantage: f32) -> f32 {
+    match player {
+        Player::Red => advantage,
+        Player::Yellow => -advantage,
+    }
+}
+
+fn cell_center(rect: egui::Rect, cell_size: f32, row: usize, col: usize) -> egui::Pos2 {
+    rect.min
+        + egui::Vec2::new(
+            (col as f32 + 0.5) * cell_size,
+            (row as f32 + 0.5) * cell_size,
+        )
+}
+
+/// Rings every winning disc and draws a bar through each line of four or more.
+fn paint_winning_line(painter: &egui::Painter, rect: egui::Rect, cell_size: f32, board: &Board) {
+    let highlight = egui::Color32::from_rgba_unmultiplied(255, 255, 255, 220);
+    for &(row, col) in board.winning_cells().unwrap_or_default() {
+        painter.circle_stroke(
+            cell_center(rect, cell_size, row, col),
+            cell_size * 0.4,
+            egui::Stroke::new(4.0, highlight),
+        );
+    }
+    for [start, end] in board.winning_lines() {
+        painter.line_segment(
+            [
+                cell_center(rect, cell_size, start.0, start.1),
+                cell_center(rect, cell_size, end.0, end.1),
+            ],
+            egui::Stroke::new(cell_size * 0.12, highlight),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curated_puzzles;
+
+    /// Plays `col` as the engine would, without a search.
+    fn engine_move(app: &mut ConnectFourApp, col: usize) {
+        let note = MoveNote {
+            by_engine: true,
+            ..MoveNote::default()
+        };
+        app.ai_move_timer = None;
+        assert!(app.play_move(col, note));
+    }
+
+    fn against_the_ai() -> ConnectFourApp {
+        let mut app = ConnectFourApp::default();
+        let engine = PlayerKind::Engine(EngineSettings::default());
+        app.start_game_from(PlayerKind::Human, engine, Board::new());
+        app
+    }
+
+    fn human_vs_human(control: Option<TimeControl>) -> ConnectFourApp {
+        let mut app = ConnectFourApp::default();
+        app.start_game_from(PlayerKind::Human, PlayerKind::Human, Board::new());
+        app.set_time_control(control);
+        app
+    }
+
+    const MINUTE: TimeControl = TimeControl {
+        initial_ms: 60_000,
+        increment_ms: 0,
+    };
+
+    fn remaining(app: &ConnectFourApp, player: Player) -> Duration {
+        app.clock.as_ref().unwrap().remaining(player)
+    }
+
+    #[test]
+    fn undo_against_the_ai_takes_back_both_moves() {
+        let mut app = against_the_ai();
+        app.human_move(3);
+        engine_move(&mut app, 3);
+        app.human_move(2);
+        engine_move(&mut app, 2);
+
+        app.undo();
+        assert_eq!(app.current_ply(), 2);
+        assert_eq!(app.board.current_player(), Player::Red);
+        assert!(app.engine_to_move().is_none());
+
+        app.redo();
+        assert_eq!(app.current_ply(), 4);
+        assert_eq!(app.board.moves(), [3, 3, 2, 2]);
+        assert!(!app.can_redo());
+    }
+
+    #[test]
+    fn undo_between_humans_takes_back_one_move() {
+        let mut app = human_vs_human(None);
+        app.human_move(3);
+        app.human_move(4);
+
+        app.undo();
+        assert_eq!(app.current_ply(), 1);
+        assert_eq!(app.board.current_player(), Player::Yellow);
+        app.redo();
+        assert_eq!(app.board.moves(), [3, 4]);
+    }
+
+    #[test]
+    fn time_used_across_undo_and_redo_is_not_refunded() {
+        let mut app = human_vs_human(Some(MINUTE));
+        app.human_move(3);
+        app.human_move(4);
+
+        // Yellow spends five seconds looking at its undone move, then replays it
+        app.undo();
+        app.clock
+            .as_mut()
+            .unwrap()
+            .charge(Player::Yellow, Duration::from_secs(5));
+        app.redo();
+        assert_eq!(remaining(&app, Player::Yellow), Duration::from_secs(55));
+        assert_eq!(remaining(&app, Player::Red), Duration::from_secs(60));
+
+        app.undo();
+        assert_eq!(remaining(&app, Player::Yellow), Duration::from_secs(55));
+    }
+
+    #[test]
+    fn undo_is_off_once_a_side_has_flagged() {
+        let mut app = human_vs_human(Some(MINUTE));
+        app.human_move(3);
+        app.human_move(4);
+        app.clock
+            .as_mut()
+            .unwrap()
+            .charge(Player::Red, Duration::from_secs(60));
+
+        assert_eq!(app.winner(), Some(Player::Yellow));
+        assert!(!app.can_undo());
+        app.undo();
+        assert_eq!(app.current_ply(), 2);
+        assert_eq!(app.winner(), Some(Player::Yellow));
+    }
+
+    #[test]
+    fn a_stepped_engine_is_timed_until_it_moves() {
+        let mut app = ConnectFourApp::default();
+        let engine = PlayerKind::Engine(EngineSettings::default());
+        app.start_game_from(engine, engine, Board::new());
+        app.set_time_control(Some(MINUTE));
+        app.paused = true;
+        app.step_requested = true;
+
+        // A search standing in for the one the step started
+        let (sender, result) = mpsc::channel();
+        app.ai_search = Some(AiSearch {
+            key: app.board.key(),
+            result,
+            stop: Arc::new(AtomicBool::new(false)),
+        });
+        app.update_clock();
+        assert_eq!(app.clock.as_ref().unwrap().running(), Some(Player::Red));
+
+        let chosen = MoveResult {
+            column: 3,
+            moves_to_win: None,
+            evaluation: Evaluation::Heuristic(0),
+        };
+        sender.send((Solver::new(), Some(chosen))).unwrap();
+        app.finish_ai_search();
+        app.update_clock();
+        assert_eq!(app.board.moves(), [3]);
+        assert!(!app.step_requested);
+        assert_eq!(app.clock.as_ref().unwrap().running(), None);
+    }
+
+    #[test]
+    fn saved_games_load_ready_to_resume() {
+        let mut app = ConnectFourApp::default();
+        let engine = EngineSettings {
+            depth: 6,
+            time_limit_ms: Some(500),
+            threads: 2,
+        };
+        let start = Board::from_moves("44").unwrap();
+        app.start_game_from(PlayerKind::Human, PlayerKind::Engine(engine), start);
+        app.set_time_control(Some(MINUTE));
+        app.human_move(2);
+        engine_move(&mut app, 4);
+        app.clock
+            .as_mut()
+            .unwrap()
+            .charge(Player::Red, Duration::from_secs(7));
+        app.human_move(3);
+
+        let path = std::env::temp_dir().join(format!("c4_game_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        app.save_game(path).unwrap();
+        let mut loaded = ConnectFourApp::default();
+        let result = loaded.load_game(path);
+        std::fs::remove_file(path).unwrap();
+        result.unwrap();
+
+        assert_eq!(loaded.game_mode, GameMode::Playing);
+        assert!(!loaded.analysis_mode);
+        assert_eq!(loaded.red, PlayerKind::Human);
+        assert_eq!(loaded.yellow, PlayerKind::Engine(engine));
+        assert_eq!(loaded.start_position.moves(), [3, 3]);
+        assert_eq!(loaded.board.moves(), [3, 3, 2, 4, 3]);
+        assert_eq!(loaded.time_control, Some(MINUTE));
+        assert_eq!(remaining(&loaded, Player::Red), Duration::from_secs(53));
+        assert_eq!(remaining(&loaded, Player::Yellow), Duration::from_secs(60));
+        // The AI is to move, so the unfinished game carries on with its reply
+        assert_eq!(loaded.engine_to_move(), Some(engine));
+        assert!(!loaded.is_game_over());
+        assert!(loaded.can_undo());
+    }
+
+    #[test]
+    fn a_failed_puzzle_cannot_be_taken_back() {
+        // Red wins in 2 by playing column 5, the defender's 4 and then 6
+        let puzzle = curated_puzzles()[0].clone();
+        let mut app = ConnectFourApp::default();
+        app.start_puzzle(puzzle.clone());
+
+        for _ in 0..2 {
+            app.human_move(0);
+            engine_move(&mut app, 0);
+        }
+        assert_eq!(app.puzzle_outcome(), Some(false));
+        assert!(!app.can_undo());
+        app.undo();
+        app.jump_to_ply(0);
+        assert_eq!(app.current_ply(), 4);
+
+        // Playing the solution after the failure doesn't count
+        app.human_move(4);
+        engine_move(&mut app, 3);
+        app.human_move(5);
+        app.update_puzzles();
+        assert_eq!(app.puzzle_outcome(), Some(false));
+        assert!(!app.puzzle_book.is_solved(&puzzle));
+
+        // Retry starts the puzzle over
+        app.start_puzzle(puzzle.clone());
+        app.human_move(4);
+        engine_move(&mut app, 3);
+        app.human_move(5);
+        app.update_puzzles();
+        assert_eq!(app.puzzle_outcome(), Some(true));
+        assert!(app.puzzle_book.is_solved(&puzzle));
+    }
+}
@@ -0,0 +1,223 @@
+//! Headless solver: prints the best move, its score, every column's score and the expected
+//! line of play for a position, as text or JSON.
+use connect_four_solver::{
+    Board, COLS, EngineSettings, Evaluation, Player, PositionAnalysis, Solver,
+};
+use serde::Serialize;
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: c4solve [--depth N] [--json] [POSITION]
+
+POSITION is a move string such as 4453 (one digit per move, 1 for the leftmost column)
+or a grid string such as ......./......./......./......./...Y.../.RYR... r (rows from
+the top, then the side to move). Without a position the empty board is solved.
+
+Scores are proven (win or loss in N moves, or a draw) when every line within the depth
+reaches the end of the game, and heuristic (+ favors the side to move) otherwise.
+
+Options:
+  -d, --depth N   Search depth in plies (default 9)
+      --json      Print the result as JSON; distances in scores are in plies
+  -h, --help      Print this help";
+
+/// Everything printed for one position. Columns are numbered from 1, as in move strings.
+#[derive(Serialize)]
+struct Report {
+    position: String, // Grid string
+    moves: String,
+    to_move: Player,
+    depth: u8,
+    winner: Option<Player>, // Set when the game is already over
+    game_over: bool,
+    best_move: Option<usize>,
+    score: Option<Evaluation>,
+    score_text: Option<String>,
+    columns: [Option<Evaluation>; COLS], // `None` for full columns
+    pv: Vec<usize>,
+}
+
+/// What the command line asks for.
+#[derive(Debug)]
+enum Command {
+    Help,
+    Solve { board: Board, depth: u8, json: bool },
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut depth = EngineSettings::default().depth;
+    let mut json = false;
+    let mut position = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--json" => json = true,
+            "-d" | "--depth" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(value @ 1..) => depth = value,
+                _ => return Err("--depth needs a number of plies from 1 to 255".to_string()),
+            },
+            _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+            // A grid string's side to move may come as a separate argument
+            _ => position.push(arg),
+        }
+    }
+
+    let board = position
+        .join(" ")
+        .trim()
+        .parse()
+        .map_err(|err| format!("invalid position: {err}"))?;
+    Ok(Command::Solve { board, depth, json })
+}
+
+fn main() -> ExitCode {
+    let (board, depth, json) = match parse_args(env::args().skip(1)) {
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Solve { board, depth, json }) => (board, depth, json),
+        Err(message) => return usage_error(&message),
+    };
+    let report = solve(&board, depth);
+
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(text) => println!("{text}"),
+            Err(err) => {
+                eprintln!("c4solve: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("c4solve: {message}\n\n{USAGE}");
+    ExitCode::from(2)
+}
+
+fn solve(board: &Board, depth: u8) -> Report {
+    let mut solver = Solver::new();
+    let analysis = PositionAnalysis {
+        key: board.key(),
+        depth,
+        columns: solver.evaluate_moves(board, depth),
+    };
+    let best = analysis.best_column();
+    // Follows the same column scores, so it starts with the best move
+    let pv = solver.principal_variation(board, depth);
+    let score = analysis.evaluation();
+
+    Report {
+        position: board.grid_string(),
+        moves: board.move_string(),
+        to_move: board.current_player(),
+        depth,
+        winner: board.winner(),
+        game_over: board.is_game_over(),
+        best_move: best.map(|col| col + 1),
+        score,
+        score_text: score.map(|eval| eval.to_string()),
+        columns: analysis.columns,
+        pv: pv.into_iter().map(|col| col + 1).collect(),
+    }
+}
+
+fn print_report(report: &Report) {
+    println!("Position:  {}", report.position);
+    if !report.moves.is_empty() {
+        println!("Moves:     {}", report.moves);
+    }
+    if report.game_over {
+        match report.winner {
+            Some(winner) => println!("Game over: {} won", winner.to_string()),
+            None => println!("Game over: draw"),
+        }
+        return;
+    }
+    println!("To move:   {}", report.to_move.to_string());
+    println!("Depth:     {}", report.depth);
+    if let (Some(col), Some(score)) = (report.best_move, &report.score_text) {
+        println!("Best move: {col} ({score})");
+    }
+    let columns: Vec<String> = report
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(col, eval)| match eval {
+            Some(eval) => format!("{}: {eval}", col + 1),
+            None => format!("{}: full", col + 1),
+        })
+        .collect();
+    println!("Columns:   {}", columns.join(", "));
+    let pv: Vec<String> = report.pv.iter().map(|col| col.to_string()).collect();
+    println!("PV:        {}", pv.join(" "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// The grid string, depth and JSON flag of a successful parse.
+    fn solve_args(args: &[&str]) -> (String, u8, bool) {
+        match parse(args) {
+            Ok(Command::Solve { board, depth, json }) => (board.grid_string(), depth, json),
+            other => panic!("{args:?} parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_move_and_grid_strings() {
+        let grid = Board::from_moves("4453").unwrap().grid_string();
+        assert_eq!(solve_args(&["-d", "5", "4453"]), (grid.clone(), 5, false));
+        // The side to move may follow the grid as its own argument
+        let (rows, side) = grid.split_once(' ').unwrap();
+        let default_depth = EngineSettings::default().depth;
+        assert_eq!(
+            solve_args(&["--json", rows, side]),
+            (grid.clone(), default_depth, true)
+        );
+        assert_eq!(solve_args(&[&grid]).0, grid);
+        assert_eq!(
+            solve_args(&[]),
+            (Board::new().grid_string(), default_depth, false)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        for depth in [
+            &["--depth"][..],
+            &["--depth", "0"],
+            &["-d", "deep"],
+            &["-d", "300"],
+        ] {
+            assert!(parse(depth).unwrap_err().contains("--depth"), "{depth:?}");
+        }
+        assert_eq!(parse(&["--fast"]).unwrap_err(), "unknown option --fast");
+        assert!(
+            parse(&["4489"])
+                .unwrap_err()
+                .starts_with("invalid position")
+        );
+        assert!(matches!(parse(&["4453", "--help"]), Ok(Command::Help)));
+    }
+
+    #[test]
+    fn report_line_starts_with_the_best_move() {
+        let board = Board::from_moves("221233541").unwrap();
+        let report = solve(&board, 4);
+        assert_eq!(report.pv.first().copied(), report.best_move);
+        assert_eq!(report.pv.len(), 5);
+    }
+}
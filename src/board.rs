@@ -1,17 +1,145 @@
 use crate::{COLS, Player, ROWS};
+use serde::{Deserialize, Deserializer, Serialize, de};
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Zobrist hashing: one random 64-bit value per (row, col, player), XORed together for every
+/// occupied cell, plus `SIDE_TO_MOVE_KEY` when Yellow is to move.
+const ZOBRIST: [[[u64; 2]; COLS]; ROWS] = zobrist_table();
+const SIDE_TO_MOVE_KEY: u64 = splitmix64(0x5eed_c4c4_0000_0000);
+
+const fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn zobrist_table() -> [[[u64; 2]; COLS]; ROWS] {
+    let mut table = [[[0; 2]; COLS]; ROWS];
+    let mut seed = 0x0c4f_0a11_5eed_0001_u64;
+    let mut row = 0;
+    while row < ROWS {
+        let mut col = 0;
+        while col < COLS {
+            let mut p = 0;
+            while p < 2 {
+                seed = splitmix64(seed);
+                table[row][col][p] = seed;
+                p += 1;
+            }
+            col += 1;
+        }
+        row += 1;
+    }
+    table
+}
+
+fn piece_key(row: usize, col: usize, player: Player) -> u64 {
+    ZOBRIST[row][col][player as usize]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Cell {
     Empty,
     Occupied(Player),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Board {
     grid: [[Cell; COLS]; ROWS],
     current_player: Player,
     game_over: bool,
     winner: Option<Player>,
+    winning_cells: Vec<(usize, usize)>,
+    moves: Vec<usize>,
+    #[serde(skip)]
+    key: u64,
+    #[serde(skip)]
+    mirror_key: u64,
+}
+
+/// Serialized form of `Board`. Nothing but the grid and moves is trusted on load: the board
+/// is rebuilt from them, and the winning cells and position keys are recomputed.
+#[derive(Deserialize)]
+struct BoardFields {
+    grid: [[Cell; COLS]; ROWS],
+    current_player: Player,
+    game_over: bool,
+    winner: Option<Player>,
+    #[serde(default)]
+    moves: Vec<usize>,
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = BoardFields::deserialize(deserializer)?;
+        Board::from_fields(fields).map_err(de::Error::custom)
+    }
+}
+
+/// Why a hand-made or pasted position can't be played from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionError {
+    FloatingPiece { row: usize, col: usize },
+    PieceCount { red: usize, yellow: usize },
+    SideToMove(Player),
+    AlreadyWon(Player),
+    BoardFull,
+    UnexpectedChar(char),
+    GridShape, // A grid string without `ROWS` rows of `COLS` cells
+    IllegalMove { ply: usize, col: usize },
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PositionError::FloatingPiece { row, col } => write!(
+                f,
+                "piece in column {}, row {} from the bottom, has an empty cell below it",
+                col + 1,
+                ROWS - row
+            ),
+            PositionError::PieceCount { red, yellow } => write!(
+                f,
+                "{red} red and {yellow} yellow pieces; counts may differ by at most one"
+            ),
+            PositionError::SideToMove(player) => write!(
+                f,
+                "{} can't be to move with these piece counts",
+                player.to_string()
+            ),
+            PositionError::AlreadyWon(player) => {
+                write!(f, "{} already has four in a row", player.to_string())
+            }
+            PositionError::BoardFull => write!(f, "the board is full"),
+            PositionError::UnexpectedChar(c) => write!(f, "unexpected character '{c}'"),
+            PositionError::GridShape => {
+                write!(
+                    f,
+                    "a grid needs {ROWS} rows of {COLS} cells separated by '/'"
+                )
+            }
+            PositionError::IllegalMove { ply, col } => {
+                write!(f, "move {ply} can't be played in column {}", col + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+/// Parses either a move string ("4453") or a grid string (rows separated by '/').
+impl FromStr for Board {
+    type Err = PositionError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.contains('/') {
+            Self::from_grid_string(text)
+        } else {
+            Self::from_moves(text)
+        }
+    }
 }
 
 impl Default for Board {
@@ -27,9 +155,202 @@ impl Board {
             current_player: Player::Red,
             game_over: false,
             winner: None,
+            winning_cells: Vec::new(),
+            moves: Vec::new(),
+            key: 0,
+            mirror_key: 0,
         }
     }
 
+    /// Builds a playable board from an arbitrary grid (row 0 is the top), checking that
+    /// every piece rests on another or the floor, that the piece counts fit `side_to_move`,
+    /// and that nobody has already won. The resulting board has no move history.
+    pub fn from_position(
+        grid: [[Cell; COLS]; ROWS],
+        side_to_move: Player,
+    ) -> Result<Self, PositionError> {
+        let mut red = 0;
+        let mut yellow = 0;
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                match cell {
+                    Cell::Occupied(Player::Red) => red += 1,
+                    Cell::Occupied(Player::Yellow) => yellow += 1,
+                    Cell::Empty => continue,
+                }
+                if row + 1 < ROWS && grid[row + 1][col] == Cell::Empty {
+                    return Err(PositionError::FloatingPiece { row, col });
+                }
+            }
+        }
+
+        // Equal counts allow either side to have started; otherwise the side behind moves
+        let allowed = match red as i32 - yellow as i32 {
+            0 => side_to_move,
+            1 => Player::Yellow,
+            -1 => Player::Red,
+            _ => return Err(PositionError::PieceCount { red, yellow }),
+        };
+        if allowed != side_to_move {
+            return Err(PositionError::SideToMove(side_to_move));
+        }
+
+        let mut board = Self::new();
+        board.grid = grid;
+        board.current_player = side_to_move;
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if let Cell::Occupied(player) = cell
+                    && !board.find_winning_cells(row, col).is_empty()
+                {
+                    return Err(PositionError::AlreadyWon(player));
+                }
+            }
+        }
+        if board.is_board_full() {
+            return Err(PositionError::BoardFull);
+        }
+        board.recompute_keys();
+        Ok(board)
+    }
+
+    /// Rebuilds a deserialized board: takes its moves back off the grid to find the position
+    /// they were played from, checks that position with `from_position`, replays the moves
+    /// and requires the result to match the saved side to move and outcome.
+    fn from_fields(fields: BoardFields) -> Result<Self, String> {
+        let mut grid = fields.grid;
+        // A game-ending move leaves the mover as the current player
+        let mut mover = if fields.game_over {
+            fields.current_player
+        } else {
+            fields.current_player.opposite()
+        };
+        for (ply, &col) in fields.moves.iter().enumerate().rev() {
+            if col >= COLS {
+                return Err(format!("move {} is off the board", ply + 1));
+            }
+            match (0..ROWS).find(|&row| grid[row][col] != Cell::Empty) {
+                Some(row) if grid[row][col] == Cell::Occupied(mover) => {
+                    grid[row][col] = Cell::Empty;
+                }
+                _ => return Err(PositionError::IllegalMove { ply: ply + 1, col }.to_string()),
+            }
+            mover = mover.opposite();
+        }
+
+        let mut board =
+            Self::from_position(grid, mover.opposite()).map_err(|err| err.to_string())?;
+        for (ply, &col) in fields.moves.iter().enumerate() {
+            if !board.make_move(col) {
+                return Err(PositionError::IllegalMove { ply: ply + 1, col }.to_string());
+            }
+        }
+        if board.current_player != fields.current_player
+            || board.game_over != fields.game_over
+            || board.winner != fields.winner
+        {
+            return Err("the side to move or result doesn't match the grid".to_string());
+        }
+        Ok(board)
+    }
+
+    /// Plays a move string such as "4453": one digit per move, 1 for the leftmost column.
+    /// Whitespace is ignored.
+    pub fn from_moves(moves: &str) -> Result<Self, PositionError> {
+        let mut board = Self::new();
+        for c in moves.chars().filter(|c| !c.is_whitespace()) {
+            let col = match c.to_digit(10) {
+                Some(digit @ 1..=7) => digit as usize - 1,
+                _ => return Err(PositionError::UnexpectedChar(c)),
+            };
+            if !board.make_move(col) {
+                let ply = board.moves.len() + 1;
+                return Err(PositionError::IllegalMove { ply, col });
+            }
+        }
+        Ok(board)
+    }
+
+    /// Reads a grid string as written by `grid_string`: rows from the top separated by
+    /// '/', with '.', 'R' and 'Y' for the cells, optionally followed by the side to move.
+    /// Without one, Red moves when the counts are equal. Checked like `from_position`.
+    pub fn from_grid_string(text: &str) -> Result<Self, PositionError> {
+        let mut parts = text.split_whitespace();
+        let rows: Vec<&str> = parts.next().unwrap_or_default().split('/').collect();
+        if rows.len() != ROWS {
+            return Err(PositionError::GridShape);
+        }
+        let mut grid = [[Cell::Empty; COLS]; ROWS];
+        let (mut red, mut yellow) = (0, 0);
+        for (cells, row) in grid.iter_mut().zip(rows) {
+            if row.chars().count() != COLS {
+                return Err(PositionError::GridShape);
+            }
+            for (cell, c) in cells.iter_mut().zip(row.chars()) {
+                *cell = match c.to_ascii_uppercase() {
+                    '.' => Cell::Empty,
+                    'R' => {
+                        red += 1;
+                        Cell::Occupied(Player::Red)
+                    }
+                    'Y' => {
+                        yellow += 1;
+                        Cell::Occupied(Player::Yellow)
+                    }
+                    _ => return Err(PositionError::UnexpectedChar(c)),
+                };
+            }
+        }
+
+        let side_to_move = match parts.next() {
+            Some("r" | "R") => Player::Red,
+            Some("y" | "Y") => Player::Yellow,
+            Some(other) => {
+                return Err(PositionError::UnexpectedChar(
+                    other.chars().next().unwrap_or(' '),
+                ));
+            }
+            None if red > yellow => Player::Yellow,
+            None => Player::Red,
+        };
+        if let Some(extra) = parts.next() {
+            return Err(PositionError::UnexpectedChar(
+                extra.chars().next().unwrap_or(' '),
+            ));
+        }
+        Self::from_position(grid, side_to_move)
+    }
+
+    /// Move string of the game so far, as read by `from_moves`. Only describes the position
+    /// for boards that started empty; see `has_move_history`.
+    pub fn move_string(&self) -> String {
+        self.moves.iter().map(|col| (col + 1).to_string()).collect()
+    }
+
+    /// The position as a single line, e.g. "......./......./......./......./......./...R... y",
+    /// as read by `from_grid_string`.
+    pub fn grid_string(&self) -> String {
+        let rows: Vec<String> = self
+            .grid
+            .iter()
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Cell::Empty => '.',
+                        Cell::Occupied(Player::Red) => 'R',
+                        Cell::Occupied(Player::Yellow) => 'Y',
+                    })
+                    .collect()
+            })
+            .collect();
+        let side = match self.current_player {
+            Player::Red => 'r',
+            Player::Yellow => 'y',
+        };
+        format!("{} {side}", rows.join("/"))
+    }
+
     pub fn make_move(&mut self, col: usize) -> bool {
         if self.game_over || col >= COLS {
             return false;
@@ -39,16 +360,21 @@ impl Board {
         for row in (0..ROWS).rev() {
             if self.grid[row][col] == Cell::Empty {
                 self.grid[row][col] = Cell::Occupied(self.current_player);
+                self.key ^= piece_key(row, col, self.current_player);
+                self.mirror_key ^= piece_key(row, COLS - 1 - col, self.current_player);
+                self.moves.push(col);
 
                 // Check for win
-                if self.check_win(row, col) {
+                let winning_cells = self.find_winning_cells(row, col);
+                if !winning_cells.is_empty() {
+                    self.winning_cells = winning_cells;
                     self.game_over = true;
                     self.winner = Some(self.current_player);
                 } else if self.is_board_full() {
                     self.game_over = true;
                     self.winner = None; // Draw
                 } else {
-                    self.current_player = self.current_player.opposite();
+                    self.switch_player();
                 }
                 return true;
             }
@@ -56,6 +382,54 @@ impl Board {
         false // Column is full
     }
 
+    /// Takes back the most recent move, returning its column. Returns `None` when there is no
+    /// recorded move to undo.
+    pub fn undo_move(&mut self) -> Option<usize> {
+        let col = self.moves.pop()?;
+        let row = (0..ROWS)
+            .find(|&row| self.grid[row][col] != Cell::Empty)
+            .expect("recorded move has a piece in its column");
+        let Cell::Occupied(player) = self.grid[row][col] else {
+            unreachable!()
+        };
+
+        self.grid[row][col] = Cell::Empty;
+        self.key ^= piece_key(row, col, player);
+        self.mirror_key ^= piece_key(row, COLS - 1 - col, player);
+        // A game-ending move leaves the mover as the current player
+        if self.current_player != player {
+            self.switch_player();
+        }
+        self.game_over = false;
+        self.winner = None;
+        self.winning_cells.clear();
+        Some(col)
+    }
+
+    fn switch_player(&mut self) {
+        self.current_player = self.current_player.opposite();
+        self.key ^= SIDE_TO_MOVE_KEY;
+        self.mirror_key ^= SIDE_TO_MOVE_KEY;
+    }
+
+    fn recompute_keys(&mut self) {
+        let side = if self.current_player == Player::Yellow {
+            SIDE_TO_MOVE_KEY
+        } else {
+            0
+        };
+        self.key = side;
+        self.mirror_key = side;
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                if let Cell::Occupied(player) = self.grid[row][col] {
+                    self.key ^= piece_key(row, col, player);
+                    self.mirror_key ^= piece_key(row, COLS - 1 - col, player);
+                }
+            }
+        }
+    }
+
     pub fn is_valid_move(&self, col: usize) -> bool {
         if self.game_over || col >= COLS {
             return false;
@@ -63,53 +437,44 @@ impl Board {
         self.grid[0][col] == Cell::Empty
     }
 
+    /// Row a disc dropped into `col` would land in, or `None` if the column is full.
+    pub fn landing_row(&self, col: usize) -> Option<usize> {
+        (0..ROWS)
+            .rev()
+            .find(|&row| self.grid[row][col] == Cell::Empty)
+    }
+
     pub fn get_valid_moves(&self) -> Vec<usize> {
         (0..COLS).filter(|&col| self.is_valid_move(col)).collect()
     }
 
-    fn check_win(&self, row: usize, col: usize) -> bool {
+    /// Collects every cell belonging to a line of four or more through `(row, col)`.
+    /// Returns an empty vector if the piece there doesn't complete a line.
+    fn find_winning_cells(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
         let player = match self.grid[row][col] {
             Cell::Occupied(p) => p,
-            Cell::Empty => return false,
+            Cell::Empty => return Vec::new(),
         };
 
-        // Check horizontal
-        if self.count_direction(row, col, 0, 1, player)
-            + self.count_direction(row, col, 0, -1, player)
-            + 1
-            >= 4
-        {
-            return true;
-        }
-
-        // Check vertical
-        if self.count_direction(row, col, 1, 0, player)
-            + self.count_direction(row, col, -1, 0, player)
-            + 1
-            >= 4
-        {
-            return true;
-        }
-
-        // Check diagonal (/)
-        if self.count_direction(row, col, 1, 1, player)
-            + self.count_direction(row, col, -1, -1, player)
-            + 1
-            >= 4
-        {
-            return true;
-        }
-
-        // Check diagonal (\)
-        if self.count_direction(row, col, 1, -1, player)
-            + self.count_direction(row, col, -1, 1, player)
-            + 1
-            >= 4
-        {
-            return true;
+        let mut cells = Vec::new();
+        // Horizontal, vertical, diagonal (/), diagonal (\)
+        for (delta_row, delta_col) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+            let forward = self.count_direction(row, col, delta_row, delta_col, player);
+            let backward = self.count_direction(row, col, -delta_row, -delta_col, player);
+            if forward + backward + 1 < 4 {
+                continue;
+            }
+            for step in -(backward as i32)..=forward as i32 {
+                let cell = (
+                    (row as i32 + step * delta_row) as usize,
+                    (col as i32 + step * delta_col) as usize,
+                );
+                if !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
         }
-
-        false
+        cells
     }
 
     fn count_direction(
@@ -148,6 +513,11 @@ impl Board {
         self.grid[row][col]
     }
 
+    /// The full grid, row 0 at the top.
+    pub fn grid(&self) -> [[Cell; COLS]; ROWS] {
+        self.grid
+    }
+
     pub fn current_player(&self) -> Player {
         self.current_player
     }
@@ -160,10 +530,356 @@ impl Board {
         self.winner
     }
 
+    /// Cells (row, col) of the winning line(s), or `None` if nobody has won.
+    /// Includes every cell of a line longer than four and of crossing lines
+    /// completed by the same move.
+    pub fn winning_cells(&self) -> Option<&[(usize, usize)]> {
+        if self.winning_cells.is_empty() {
+            None
+        } else {
+            Some(&self.winning_cells)
+        }
+    }
+
+    /// First and last cell of each winning line, so every line of four or more can be drawn
+    /// as one segment. Empty if nobody has won.
+    pub fn winning_lines(&self) -> Vec<[(usize, usize); 2]> {
+        let cells = &self.winning_cells;
+        let contains = |row: i32, col: i32| {
+            row >= 0 && col >= 0 && cells.contains(&(row as usize, col as usize))
+        };
+        // Crossing lines share one cell, so each maximal run along a direction is one line
+        let mut lines = Vec::new();
+        for &(row, col) in cells {
+            for (delta_row, delta_col) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                let (r, c) = (row as i32, col as i32);
+                if contains(r - delta_row, c - delta_col) {
+                    continue; // Not the start of a run
+                }
+                let mut len = 1;
+                while contains(r + len * delta_row, c + len * delta_col) {
+                    len += 1;
+                }
+                if len >= 4 {
+                    let end = (
+                        (r + (len - 1) * delta_row) as usize,
+                        (c + (len - 1) * delta_col) as usize,
+                    );
+                    lines.push([(row, col), end]);
+                }
+            }
+        }
+        lines
+    }
+
+    /// Columns played since the board was created or reset, oldest first.
+    pub fn moves(&self) -> &[usize] {
+        &self.moves
+    }
+
+    /// Whether `moves` accounts for every disc, i.e. the board was played from empty and
+    /// `move_string` describes the position. False for boards set up from a grid.
+    pub fn has_move_history(&self) -> bool {
+        let discs = self
+            .grid
+            .iter()
+            .flatten()
+            .filter(|&&cell| cell != Cell::Empty);
+        discs.count() == self.moves.len()
+    }
+
+    /// 64-bit Zobrist key of the position (pieces and side to move), maintained
+    /// incrementally by `make_move` and `undo_move`.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// Position key that is identical for a position and its left-right mirror image.
+    pub fn canonical_key(&self) -> u64 {
+        self.key.min(self.mirror_key)
+    }
+
     pub fn reset(&mut self) {
-        self.grid = [[Cell::Empty; COLS]; ROWS];
-        self.current_player = Player::Red;
-        self.game_over = false;
-        self.winner = None;
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_board(a: &Board, b: &Board) {
+        assert_eq!(a.grid(), b.grid());
+        assert_eq!(a.moves(), b.moves());
+        assert_eq!(a.current_player(), b.current_player());
+        assert_eq!(a.is_game_over(), b.is_game_over());
+        assert_eq!(a.winner(), b.winner());
+        assert_eq!(a.winning_cells(), b.winning_cells());
+        assert_eq!(a.key(), b.key());
+    }
+
+    fn round_trip(board: &Board) -> Board {
+        let json = serde_json::to_string(board).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn serde_round_trips_game_in_progress() {
+        let mut board = Board::new();
+        for col in [3, 3, 2, 4] {
+            board.make_move(col);
+        }
+        assert_same_board(&round_trip(&board), &board);
+    }
+
+    #[test]
+    fn serde_round_trips_finished_game() {
+        let mut board = Board::new();
+        for col in [0, 1, 0, 1, 0, 1, 0] {
+            board.make_move(col);
+        }
+        assert_eq!(board.winner(), Some(Player::Red));
+        assert_same_board(&round_trip(&board), &board);
+    }
+
+    fn played(moves: &[usize]) -> Board {
+        let mut board = Board::new();
+        for &col in moves {
+            assert!(board.make_move(col));
+        }
+        board
+    }
+
+    #[test]
+    fn key_depends_on_position_not_move_order() {
+        assert_eq!(played(&[0, 1, 2]).key(), played(&[2, 1, 0]).key());
+        assert_ne!(played(&[0, 1, 2]).key(), played(&[0, 2, 1]).key());
+    }
+
+    #[test]
+    fn key_matches_recomputed_key() {
+        let board = played(&[3, 3, 2, 4, 4]);
+        let rebuilt = Board::from_position(board.grid(), board.current_player()).unwrap();
+        assert_eq!(rebuilt.key(), board.key());
+        assert_eq!(rebuilt.canonical_key(), board.canonical_key());
+    }
+
+    #[test]
+    fn key_includes_side_to_move() {
+        let board = played(&[3, 3]);
+        let other_side = Board::from_position(board.grid(), Player::Yellow).unwrap();
+        assert_ne!(other_side.key(), board.key());
+    }
+
+    #[test]
+    fn canonical_key_is_shared_by_mirror_images() {
+        let board = played(&[0, 1, 1, 3]);
+        let mirrored = played(&[6, 5, 5, 3]);
+        assert_ne!(board.key(), mirrored.key());
+        assert_eq!(board.canonical_key(), mirrored.canonical_key());
+        assert_eq!(board.mirror_key, mirrored.key());
+    }
+
+    #[test]
+    fn undo_move_restores_previous_position() {
+        let before = played(&[3, 2, 3]);
+        let mut board = before.clone();
+        board.make_move(4);
+        assert_eq!(board.undo_move(), Some(4));
+        assert_same_board(&board, &before);
+        assert_eq!(board.canonical_key(), before.canonical_key());
+    }
+
+    #[test]
+    fn undo_move_reopens_finished_game() {
+        let mut board = played(&[0, 1, 0, 1, 0, 1, 0]);
+        assert_eq!(board.undo_move(), Some(0));
+        assert_same_board(&board, &played(&[0, 1, 0, 1, 0, 1]));
+        assert_eq!(Board::new().undo_move(), None);
+    }
+
+    fn tampered(
+        board: &Board,
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> serde_json::Result<Board> {
+        let mut value = serde_json::to_value(board).unwrap();
+        edit(&mut value);
+        serde_json::from_value(value)
+    }
+
+    #[test]
+    fn deserialize_rebuilds_keys_and_winning_cells() {
+        let board = played(&[0, 1, 0, 1, 0, 1, 0]);
+        let loaded = tampered(&board, |value| {
+            value["winning_cells"] = serde_json::json!([])
+        })
+        .unwrap();
+        assert_same_board(&loaded, &board);
+    }
+
+    #[test]
+    fn deserialize_rejects_inconsistent_boards() {
+        let board = played(&[3, 3, 2]);
+        // A move whose disc isn't on the grid
+        assert!(
+            tampered(&board, |value| value["moves"] =
+                serde_json::json!([3, 3, 1]))
+            .is_err()
+        );
+        assert!(
+            tampered(&board, |value| value["moves"] =
+                serde_json::json!([3, 3, 9]))
+            .is_err()
+        );
+        // The wrong side to move, or a result the grid doesn't show
+        assert!(
+            tampered(&board, |value| value["current_player"] =
+                serde_json::json!("Red"))
+            .is_err()
+        );
+        assert!(
+            tampered(&board, |value| {
+                value["game_over"] = serde_json::json!(true);
+                value["winner"] = serde_json::json!("Red");
+            })
+            .is_err()
+        );
+        // A floating disc
+        assert!(
+            tampered(&board, |value| {
+                value["grid"][0][0] = serde_json::json!({ "Occupied": "Yellow" });
+                value["moves"] = serde_json::json!([]);
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn winning_lines_spans_each_line_once() {
+        assert!(played(&[3, 3]).winning_lines().is_empty());
+        assert_eq!(
+            played(&[0, 1, 0, 1, 0, 1, 0]).winning_lines(),
+            vec![[(2, 0), (5, 0)]]
+        );
+        // Five in a row is drawn as one segment
+        assert_eq!(
+            played(&[0, 0, 1, 1, 3, 3, 4, 4, 2]).winning_lines(),
+            vec![[(5, 0), (5, 4)]]
+        );
+    }
+
+    #[test]
+    fn winning_lines_splits_crossing_lines() {
+        let mut board =
+            Board::from_grid_string("......./......./RRR..../YYYR.../RYYR.../YRYRY.. r").unwrap();
+        board.make_move(3);
+        let mut lines = board.winning_lines();
+        lines.sort();
+        assert_eq!(lines, vec![[(2, 0), (2, 3)], [(2, 3), (5, 3)]]);
+    }
+
+    #[test]
+    fn from_moves_round_trips_move_string() {
+        let board = Board::from_moves("4453 12").unwrap();
+        assert_same_board(&board, &played(&[3, 3, 4, 2, 0, 1]));
+        assert_eq!(board.move_string(), "445312");
+        assert_eq!(Board::from_moves("").unwrap().moves(), &[] as &[usize]);
+    }
+
+    #[test]
+    fn move_history_covers_only_boards_played_from_empty() {
+        let mut board = Board::from_moves("4453").unwrap();
+        assert!(board.has_move_history());
+        let mut from_grid = Board::from_grid_string(&board.grid_string()).unwrap();
+        assert!(!from_grid.has_move_history());
+        // Moves played on top of a set-up position still leave the earlier discs unaccounted
+        board.make_move(0);
+        from_grid.make_move(0);
+        assert!(board.has_move_history());
+        assert!(!from_grid.has_move_history());
+        assert!(
+            Board::from_grid_string(&Board::new().grid_string())
+                .unwrap()
+                .has_move_history()
+        );
+    }
+
+    #[test]
+    fn from_moves_rejects_bad_moves() {
+        assert_eq!(
+            Board::from_moves("48").unwrap_err(),
+            PositionError::UnexpectedChar('8')
+        );
+        assert_eq!(
+            Board::from_moves("1111111").unwrap_err(),
+            PositionError::IllegalMove { ply: 7, col: 0 }
+        );
+        // Nothing can be played once the game is over
+        assert_eq!(
+            Board::from_moves("12121213").unwrap_err(),
+            PositionError::IllegalMove { ply: 8, col: 2 }
+        );
+    }
+
+    #[test]
+    fn from_grid_string_round_trips_grid_string() {
+        let board = played(&[3, 3, 4, 2, 0]);
+        let text = board.grid_string();
+        assert_eq!(text, "......./......./......./......./...Y.../R.YRR.. y");
+        let parsed = Board::from_grid_string(&text).unwrap();
+        assert_eq!(parsed.grid(), board.grid());
+        assert_eq!(parsed.current_player(), Player::Yellow);
+        assert_eq!(parsed.key(), board.key());
+        assert_eq!(parsed.grid_string(), text);
+    }
+
+    #[test]
+    fn from_grid_string_infers_side_to_move() {
+        let red_ahead =
+            Board::from_grid_string("......./......./......./......./......./...r...").unwrap();
+        assert_eq!(red_ahead.current_player(), Player::Yellow);
+        let even =
+            Board::from_grid_string("......./......./......./......./......./...RY..").unwrap();
+        assert_eq!(even.current_player(), Player::Red);
+    }
+
+    #[test]
+    fn from_grid_string_rejects_bad_grids() {
+        let empty = "......./......./......./......./.......";
+        assert_eq!(
+            Board::from_grid_string(&format!("{empty}/......")).unwrap_err(),
+            PositionError::GridShape
+        );
+        assert_eq!(
+            Board::from_grid_string(empty).unwrap_err(),
+            PositionError::GridShape
+        );
+        assert_eq!(
+            Board::from_grid_string(&format!("{empty}/...X...")).unwrap_err(),
+            PositionError::UnexpectedChar('X')
+        );
+        assert_eq!(
+            Board::from_grid_string(&format!("{empty}/....... r extra")).unwrap_err(),
+            PositionError::UnexpectedChar('e')
+        );
+        assert_eq!(
+            Board::from_grid_string("R....../......./......./......./......./....... y")
+                .unwrap_err(),
+            PositionError::FloatingPiece { row: 0, col: 0 }
+        );
+        assert_eq!(
+            Board::from_grid_string(&format!("{empty}/RR..... y")).unwrap_err(),
+            PositionError::PieceCount { red: 2, yellow: 0 }
+        );
+    }
+
+    #[test]
+    fn from_str_reads_either_format() {
+        let from_moves: Board = "4453".parse().unwrap();
+        let from_grid: Board = from_moves.grid_string().parse().unwrap();
+        assert_eq!(from_moves.moves(), &[3, 3, 4, 2]);
+        assert!(from_grid.moves().is_empty());
+        assert_eq!(from_grid.key(), from_moves.key());
+        assert!("44x".parse::<Board>().is_err());
     }
 }
@@ -0,0 +1,222 @@
+use crate::Player;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Chess-style time control: each side starts with `initial_ms` and gains `increment_ms`
+/// after every move it makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub initial_ms: u64,
+    pub increment_ms: u64,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self {
+            initial_ms: 3 * 60 * 1000,
+            increment_ms: 2000,
+        }
+    }
+}
+
+/// Both sides' clocks. At most one runs at a time; a side whose time reaches zero has lost.
+/// Only the remaining times are saved, so a loaded clock starts stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameClock {
+    control: TimeControl,
+    red_ms: u64,
+    yellow_ms: u64,
+    #[serde(skip)]
+    running: Option<(Player, Instant)>, // Side being timed and when its turn started
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            red_ms: control.initial_ms,
+            yellow_ms: control.initial_ms,
+            running: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// Side whose clock is running, if any.
+    pub fn running(&self) -> Option<Player> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// Time left for `player`, counting the turn in progress.
+    pub fn remaining(&self, player: Player) -> Duration {
+        let stored = Duration::from_millis(match player {
+            Player::Red => self.red_ms,
+            Player::Yellow => self.yellow_ms,
+        });
+        match self.running {
+            Some((running, started)) if running == player => {
+                stored.saturating_sub(started.elapsed())
+            }
+            _ => stored,
+        }
+    }
+
+    /// The side that has run out of time, if any.
+    pub fn flagged(&self) -> Option<Player> {
+        [Player::Red, Player::Yellow]
+            .into_iter()
+            .find(|&player| self.remaining(player).is_zero())
+    }
+
+    /// Runs `player`'s clock, stopping the other side's. Does nothing if it already runs.
+    pub fn start(&mut self, player: Player) {
+        if self.running() == Some(player) {
+            return;
+        }
+        self.stop();
+        self.running = Some((player, Instant::now()));
+    }
+
+    /// Stops whichever clock is running, keeping the time it used.
+    pub fn stop(&mut self) {
+        if let Some((player, _)) = self.running {
+            self.set_remaining(player, self.remaining(player));
+            self.running = None;
+        }
+    }
+
+    /// Ends `player`'s turn after a move: stops the clock and adds the increment, unless
+    /// the move came too late.
+    pub fn press(&mut self, player: Player) {
+        self.stop();
+        let remaining = self.remaining(player);
+        if !remaining.is_zero() {
+            let increment = Duration::from_millis(self.control.increment_ms);
+            self.set_remaining(player, remaining + increment);
+        }
+    }
+
+    /// Takes `used` off `player`'s time, e.g. time spent before the clock was swapped for
+    /// this one. The remaining time stops at zero.
+    pub fn charge(&mut self, player: Player, used: Duration) {
+        self.set_remaining(player, self.remaining(player).saturating_sub(used));
+    }
+
+    /// How long an engine with `player`'s clock should spend on its next move: an even
+    /// share of what's left over an expected number of moves, plus most of the increment,
+    /// but never more than half the remaining time.
+    pub fn move_budget(&self, player: Player) -> Duration {
+        const EXPECTED_MOVES: u32 = 15;
+        let remaining = self.remaining(player);
+        let increment = Duration::from_millis(self.control.increment_ms);
+        (remaining / EXPECTED_MOVES + increment * 3 / 4).min(remaining / 2)
+    }
+
+    fn set_remaining(&mut self, player: Player, remaining: Duration) {
+        let millis = remaining.as_millis() as u64;
+        match player {
+            Player::Red => self.red_ms = millis,
+            Player::Yellow => self.yellow_ms = millis,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const CONTROL: TimeControl = TimeControl {
+        initial_ms: 60_000,
+        increment_ms: 2000,
+    };
+
+    #[test]
+    fn only_the_running_side_loses_time() {
+        let mut clock = GameClock::new(CONTROL);
+        clock.start(Player::Red);
+        thread::sleep(Duration::from_millis(20));
+        clock.start(Player::Yellow);
+        assert_eq!(clock.running(), Some(Player::Yellow));
+        clock.stop();
+        assert_eq!(clock.running(), None);
+
+        let red = clock.remaining(Player::Red);
+        assert!(red <= Duration::from_millis(59_980));
+        assert!(clock.remaining(Player::Yellow) > red);
+        // A stopped clock keeps its time
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.remaining(Player::Red), red);
+    }
+
+    #[test]
+    fn press_adds_the_increment() {
+        let mut clock = GameClock::new(CONTROL);
+        clock.start(Player::Red);
+        clock.press(Player::Red);
+        assert_eq!(clock.running(), None);
+        let red = clock.remaining(Player::Red);
+        assert!(red > Duration::from_millis(61_000));
+        assert!(red <= Duration::from_millis(62_000));
+        assert_eq!(
+            clock.remaining(Player::Yellow),
+            Duration::from_millis(60_000)
+        );
+    }
+
+    #[test]
+    fn side_out_of_time_is_flagged_without_increment() {
+        let mut clock = GameClock::new(TimeControl {
+            initial_ms: 10,
+            increment_ms: 2000,
+        });
+        assert_eq!(clock.flagged(), None);
+        clock.start(Player::Yellow);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(clock.flagged(), Some(Player::Yellow));
+        clock.press(Player::Yellow);
+        assert!(clock.remaining(Player::Yellow).is_zero());
+    }
+
+    #[test]
+    fn charge_takes_time_off_one_side() {
+        let mut clock = GameClock::new(CONTROL);
+        clock.charge(Player::Yellow, Duration::from_millis(1500));
+        assert_eq!(
+            clock.remaining(Player::Yellow),
+            Duration::from_millis(58_500)
+        );
+        assert_eq!(clock.remaining(Player::Red), Duration::from_millis(60_000));
+        clock.charge(Player::Yellow, Duration::from_secs(120));
+        assert_eq!(clock.flagged(), Some(Player::Yellow));
+    }
+
+    #[test]
+    fn move_budget_never_exceeds_half_the_remaining_time() {
+        let clock = GameClock::new(CONTROL);
+        let budget = clock.move_budget(Player::Red);
+        assert_eq!(budget, Duration::from_millis(60_000 / 15 + 1500));
+
+        let short = GameClock::new(TimeControl {
+            initial_ms: 1000,
+            increment_ms: 5000,
+        });
+        assert_eq!(short.move_budget(Player::Red), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn saved_clock_loads_stopped() {
+        let mut clock = GameClock::new(CONTROL);
+        clock.start(Player::Red);
+        let loaded: GameClock =
+            serde_json::from_str(&serde_json::to_string(&clock).unwrap()).unwrap();
+        assert_eq!(loaded.running(), None);
+        assert_eq!(loaded.control(), CONTROL);
+        assert_eq!(
+            loaded.remaining(Player::Yellow),
+            Duration::from_millis(60_000)
+        );
+    }
+}
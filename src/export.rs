@@ -0,0 +1,341 @@
+use crate::{Board, COLS, Cell, Palette, Player, ROWS};
+use image::{ImageFormat, ImageResult, Rgba, RgbaImage};
+use std::path::Path;
+
+/// What `render_board` draws, and how large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    pub cell_size: u32, // Pixels per board cell
+    pub palette: Palette,
+    pub winning_line: bool,
+    pub move_numbers: bool, // Number the discs played since the board was created
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            cell_size: 80,
+            palette: Palette::CLASSIC,
+            winning_line: true,
+            move_numbers: false,
+        }
+    }
+}
+
+/// Renders `board` as it looks in the GUI, one `cell_size` square per cell.
+pub fn render_board(board: &Board, options: &ImageOptions) -> RgbaImage {
+    let cell_size = options.cell_size.max(8);
+    let cell = cell_size as f32;
+    let palette = options.palette;
+    let mut image = RgbaImage::from_pixel(
+        COLS as u32 * cell_size,
+        ROWS as u32 * cell_size,
+        opaque(palette.board),
+    );
+
+    let numbers = if options.move_numbers {
+        move_numbers(board)
+    } else {
+        [[None; COLS]; ROWS]
+    };
+    let radius = cell * 0.35;
+    let rim = (cell * 0.025).max(1.0);
+    for (row, cells) in board.grid().iter().enumerate() {
+        for (col, &cell_state) in cells.iter().enumerate() {
+            let center = cell_center(cell, row, col);
+            let (fill, edge) = match cell_state {
+                Cell::Empty => ([255, 255, 255], [160, 160, 160]),
+                Cell::Occupied(Player::Red) => (palette.red, shade(palette.red)),
+                Cell::Occupied(Player::Yellow) => (palette.yellow, shade(palette.yellow)),
+            };
+            fill_circle(&mut image, center, radius + rim, opaque(edge));
+            fill_circle(&mut image, center, radius - rim, opaque(fill));
+            if let Some(number) = numbers[row][col] {
+                let ink = if luminance(fill) > 150.0 {
+                    [30, 30, 30]
+                } else {
+                    [255, 255, 255]
+                };
+                draw_number(&mut image, center, cell, number, opaque(ink));
+            }
+        }
+    }
+
+    if options.winning_line {
+        let highlight = Rgba([255, 255, 255, 220]);
+        for &(row, col) in board.winning_cells().unwrap_or_default() {
+            let center = cell_center(cell, row, col);
+            let width = cell * 0.05;
+            paint(&mut image, center, cell * 0.4 + width, highlight, |x, y| {
+                (distance((x, y), center) - cell * 0.4).abs() - width / 2.0
+            });
+        }
+        for [start, end] in board.winning_lines() {
+            let (a, b) = (
+                cell_center(cell, start.0, start.1),
+                cell_center(cell, end.0, end.1),
+            );
+            let half_width = cell * 0.06;
+            let mid = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+            let reach = distance(a, b) / 2.0 + half_width;
+            paint(&mut image, mid, reach, highlight, |x, y| {
+                segment_distance((x, y), a, b) - half_width
+            });
+        }
+    }
+    image
+}
+
+/// Renders `board` with `render_board` and writes it to `path` as a PNG.
+pub fn export_png(
+    board: &Board,
+    path: impl AsRef<Path>,
+    options: &ImageOptions,
+) -> ImageResult<()> {
+    render_board(board, options).save_with_format(path, ImageFormat::Png)
+}
+
+/// Move number of every disc played since the board was created, found by taking the
+/// moves back one at a time. Discs of the starting position get `None`.
+fn move_numbers(board: &Board) -> [[Option<usize>; COLS]; ROWS] {
+    let mut numbers = [[None; COLS]; ROWS];
+    let mut board = board.clone();
+    let mut number = board.moves().len();
+    while let Some(col) = board.undo_move() {
+        if let Some(row) = board.landing_row(col) {
+            numbers[row][col] = Some(number);
+        }
+        number -= 1;
+    }
+    numbers
+}
+
+fn cell_center(cell: f32, row: usize, col: usize) -> (f32, f32) {
+    ((col as f32 + 0.5) * cell, (row as f32 + 0.5) * cell)
+}
+
+fn opaque([r, g, b]: [u8; 3]) -> Rgba<u8> {
+    Rgba([r, g, b, 255])
+}
+
+/// The disc's rim color, as in the GUI.
+fn shade(color: [u8; 3]) -> [u8; 3] {
+    color.map(|channel| (channel as f32 * 0.8) as u8)
+}
+
+fn luminance([r, g, b]: [u8; 3]) -> f32 {
+    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn segment_distance(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    distance(point, (a.0 + t * dx, a.1 + t * dy))
+}
+
+fn fill_circle(image: &mut RgbaImage, center: (f32, f32), radius: f32, color: Rgba<u8>) {
+    paint(image, center, radius, color, |x, y| {
+        distance((x, y), center) - radius
+    });
+}
+
+/// Blends `color` into every pixel within `reach` of `center`, weighted by how much of the
+/// pixel lies inside the shape. `signed_distance` is negative inside the shape, so edges
+/// get about one pixel of anti-aliasing.
+fn paint(
+    image: &mut RgbaImage,
+    center: (f32, f32),
+    reach: f32,
+    color: Rgba<u8>,
+    signed_distance: impl Fn(f32, f32) -> f32,
+) {
+    let (width, height) = image.dimensions();
+    let x_range =
+        (center.0 - reach - 1.0).max(0.0) as u32..((center.0 + reach + 1.0) as u32).min(width);
+    let y_range =
+        (center.1 - reach - 1.0).max(0.0) as u32..((center.1 + reach + 1.0) as u32).min(height);
+    for y in y_range {
+        for x in x_range.clone() {
+            let coverage = (0.5 - signed_distance(x as f32 + 0.5, y as f32 + 0.5)).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                blend(image.get_pixel_mut(x, y), color, coverage);
+            }
+        }
+    }
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, coverage: f32) {
+    let alpha = coverage * color[3] as f32 / 255.0;
+    for channel in 0..3 {
+        let mixed = pixel[channel] as f32 * (1.0 - alpha) + color[channel] as f32 * alpha;
+        pixel[channel] = mixed.round() as u8;
+    }
+}
+
+/// 3x5 bitmap digits, one bit per pixel, rows top to bottom with the leftmost pixel in the
+/// highest bit.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Draws `number` centered on `center` with the bitmap font, scaled to the cell size.
+fn draw_number(
+    image: &mut RgbaImage,
+    center: (f32, f32),
+    cell: f32,
+    number: usize,
+    color: Rgba<u8>,
+) {
+    let digits: Vec<usize> = number
+        .to_string()
+        .bytes()
+        .map(|digit| (digit - b'0') as usize)
+        .collect();
+    let scale = (cell / 14.0).floor().max(1.0);
+    // Digits are three pixels wide with one pixel between them
+    let width = (digits.len() * 4 - 1) as f32 * scale;
+    let left = (center.0 - width / 2.0).round();
+    let top = (center.1 - 2.5 * scale).round();
+    for (index, &digit) in digits.iter().enumerate() {
+        for (row, bits) in DIGITS[digit].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                let x = left + (index * 4 + column) as f32 * scale;
+                let y = top + row as f32 * scale;
+                for py in y as u32..(y + scale) as u32 {
+                    for px in x as u32..(x + scale) as u32 {
+                        if px < image.width() && py < image.height() {
+                            image.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(cell_size: u32, move_numbers: bool) -> ImageOptions {
+        ImageOptions {
+            cell_size,
+            move_numbers,
+            ..ImageOptions::default()
+        }
+    }
+
+    /// Pixel at the center of a cell.
+    fn center_pixel(image: &RgbaImage, cell_size: u32, row: usize, col: usize) -> Rgba<u8> {
+        let (x, y) = cell_center(cell_size as f32, row, col);
+        *image.get_pixel(x as u32, y as u32)
+    }
+
+    #[test]
+    fn renders_one_square_per_cell_in_the_palette_colors() {
+        let board = Board::from_moves("4453").unwrap();
+        let image = render_board(&board, &options(20, false));
+        assert_eq!(image.dimensions(), (140, 120));
+
+        let palette = Palette::CLASSIC;
+        assert_eq!(*image.get_pixel(0, 0), opaque(palette.board));
+        assert_eq!(center_pixel(&image, 20, 5, 3), opaque(palette.red));
+        assert_eq!(center_pixel(&image, 20, 4, 3), opaque(palette.yellow));
+        assert_eq!(center_pixel(&image, 20, 5, 2), opaque(palette.yellow));
+        assert_eq!(center_pixel(&image, 20, 0, 0), opaque([255, 255, 255]));
+        // Tiny cells are clamped to a drawable size
+        assert_eq!(render_board(&board, &options(1, false)).width(), 56);
+    }
+
+    #[test]
+    fn move_numbers_skip_discs_of_the_starting_position() {
+        let numbered = move_numbers(&Board::from_moves("4453").unwrap());
+        assert_eq!(numbered[5][3], Some(1));
+        assert_eq!(numbered[4][3], Some(2));
+        assert_eq!(numbered[5][4], Some(3));
+        assert_eq!(numbered[5][2], Some(4));
+        assert_eq!(numbered[3][3], None);
+
+        let mut board =
+            Board::from_grid_string("......./......./......./......./......./...R... y").unwrap();
+        board.make_move(3);
+        let numbered = move_numbers(&board);
+        assert_eq!(numbered[5][3], None);
+        assert_eq!(numbered[4][3], Some(1));
+    }
+
+    #[test]
+    fn move_numbers_are_drawn_in_contrasting_ink() {
+        let board = Board::from_moves("44").unwrap();
+        let plain = render_board(&board, &options(40, false));
+        let numbered = render_board(&board, &options(40, true));
+        // The bitmap "1" is two pixels per dot here; its top dot is the middle of three,
+        // drawn in white on the dark red disc
+        let (x, y) = cell_center(40.0, 5, 3);
+        let (left, top) = ((x - 3.0).round() as u32, (y - 5.0).round() as u32);
+        assert_eq!(*numbered.get_pixel(left + 2, top), opaque([255, 255, 255]));
+        assert_eq!(*numbered.get_pixel(left, top), *plain.get_pixel(left, top));
+        // The "2" on the light yellow disc is dark
+        let (x, y) = cell_center(40.0, 4, 3);
+        let (left, top) = ((x - 3.0).round() as u32, (y - 5.0).round() as u32);
+        assert_eq!(*numbered.get_pixel(left, top), opaque([30, 30, 30]));
+        assert_eq!(
+            center_pixel(&plain, 40, 0, 0),
+            center_pixel(&numbered, 40, 0, 0)
+        );
+    }
+
+    #[test]
+    fn winning_line_is_highlighted_only_when_asked() {
+        let board = Board::from_moves("1212121").unwrap();
+        let plain = render_board(
+            &board,
+            &ImageOptions {
+                winning_line: false,
+                ..options(20, false)
+            },
+        );
+        let highlighted = render_board(&board, &options(20, false));
+        assert_eq!(center_pixel(&plain, 20, 5, 0), opaque(Palette::CLASSIC.red));
+        assert_ne!(
+            center_pixel(&highlighted, 20, 4, 0),
+            center_pixel(&plain, 20, 4, 0)
+        );
+        assert_eq!(
+            center_pixel(&highlighted, 20, 5, 1),
+            center_pixel(&plain, 20, 5, 1)
+        );
+    }
+
+    #[test]
+    fn export_png_writes_the_rendered_image() {
+        let board = Board::from_moves("4453").unwrap();
+        let options = options(16, true);
+        let path = std::env::temp_dir().join(format!("c4_export_{}.png", std::process::id()));
+        export_png(&board, &path, &options).unwrap();
+        let loaded = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, render_board(&board, &options));
+    }
+}
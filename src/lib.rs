@@ -1,12 +1,30 @@
+pub mod analysis;
 pub mod app;
 pub mod board;
+pub mod clock;
+pub mod export;
+pub mod opening;
 pub mod player;
+pub mod puzzle;
+pub mod record;
+pub mod review;
+pub mod settings;
 pub mod solver;
+pub mod stats;
 
+pub use analysis::{Analyzer, PositionAnalysis};
 pub use app::ConnectFourApp;
-pub use board::{Board, Cell};
+pub use board::{Board, Cell, PositionError};
+pub use clock::{GameClock, TimeControl};
+pub use export::{ImageOptions, export_png, render_board};
+pub use opening::{Handicap, OPENINGS, Opening};
 pub use player::Player;
-pub use solver::{MoveResult, Solver};
+pub use puzzle::{Puzzle, PuzzleBook, curated_puzzles, generate_puzzles};
+pub use record::{EngineSettings, GameRecord, GameResult, PlayerKind};
+pub use review::{MoveQuality, MoveReview, review_game};
+pub use settings::{Palette, PaletteChoice, Settings, Theme};
+pub use solver::{Evaluation, MoveResult, Solver};
+pub use stats::{ProfileStats, Statistics, Tally};
 
 pub const ROWS: usize = 6;
 pub const COLS: usize = 7;
@@ -64,6 +64,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Connect Four Solver",
         options,
-        Box::new(|_cc| Ok(Box::<ConnectFourApp>::default())),
+        Box::new(|cc| Ok(Box::new(ConnectFourApp::new(cc)))),
     )
 }
@@ -0,0 +1,165 @@
+use crate::{Board, EngineSettings};
+use serde::{Deserialize, Serialize};
+
+/// A well-known start: the first few moves of a game as a move string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opening {
+    pub name: &'static str,
+    pub moves: &'static str, // As read by `Board::from_moves`
+}
+
+impl Opening {
+    pub fn board(&self) -> Board {
+        Board::from_moves(self.moves).expect("built-in openings are legal")
+    }
+}
+
+/// Openings offered on the setup screen. With perfect play Red wins after the center
+/// opening, draws after starting next to it and loses after starting further out.
+pub const OPENINGS: [Opening; 8] = [
+    Opening {
+        name: "Center",
+        moves: "4",
+    },
+    Opening {
+        name: "Center, stacked reply",
+        moves: "44",
+    },
+    Opening {
+        name: "Center, adjacent reply",
+        moves: "43",
+    },
+    Opening {
+        name: "Center, edge reply",
+        moves: "41",
+    },
+    Opening {
+        name: "Off-center",
+        moves: "3",
+    },
+    Opening {
+        name: "Off-center, center reply",
+        moves: "34",
+    },
+    Opening {
+        name: "Edge",
+        moves: "1",
+    },
+    Opening {
+        name: "Edge, center reply",
+        moves: "14",
+    },
+];
+
+/// Head start for the human in a game against the AI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Handicap {
+    #[default]
+    None,
+    ExtraMove, // The human also places the AI's first disc
+    WeakStart, // The AI plays its first few moves with a shallow search
+}
+
+impl Handicap {
+    pub const ALL: [Handicap; 3] = [Handicap::None, Handicap::ExtraMove, Handicap::WeakStart];
+
+    /// Number of the AI's moves played with a shallow search under `WeakStart`.
+    pub const WEAK_START_MOVES: usize = 3;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Handicap::None => "None",
+            Handicap::ExtraMove => "Extra move",
+            Handicap::WeakStart => "Weak start",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Handicap::None => "The AI plays at full strength from its first move",
+            Handicap::ExtraMove => "You choose where the AI's first disc goes",
+            Handicap::WeakStart => "The AI searches three plies deep for its first three moves",
+        }
+    }
+
+    /// Settings for the AI's next move after it has played `ai_moves` moves, or `None` when
+    /// the human places that disc instead.
+    pub fn engine_for_move(
+        self,
+        engine: EngineSettings,
+        ai_moves: usize,
+    ) -> Option<EngineSettings> {
+        match self {
+            Handicap::ExtraMove if ai_moves == 0 => None,
+            Handicap::WeakStart if ai_moves < Self::WEAK_START_MOVES => Some(EngineSettings {
+                depth: engine.depth.min(2),
+                time_limit_ms: None,
+                ..engine
+            }),
+            _ => Some(engine),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGINE: EngineSettings = EngineSettings {
+        depth: 9,
+        time_limit_ms: Some(1000),
+        threads: 4,
+    };
+
+    #[test]
+    fn openings_are_legal_and_unfinished() {
+        for opening in OPENINGS {
+            let board = opening.board();
+            assert_eq!(board.move_string(), opening.moves, "{}", opening.name);
+            assert!(!board.is_game_over());
+        }
+    }
+
+    #[test]
+    fn no_handicap_keeps_the_engine() {
+        for ai_moves in 0..5 {
+            assert_eq!(
+                Handicap::None.engine_for_move(ENGINE, ai_moves),
+                Some(ENGINE)
+            );
+        }
+    }
+
+    #[test]
+    fn extra_move_hands_over_only_the_first_disc() {
+        assert_eq!(Handicap::ExtraMove.engine_for_move(ENGINE, 0), None);
+        assert_eq!(Handicap::ExtraMove.engine_for_move(ENGINE, 1), Some(ENGINE));
+    }
+
+    #[test]
+    fn weak_start_searches_shallow_for_the_first_moves() {
+        let weak = EngineSettings {
+            depth: 2,
+            time_limit_ms: None,
+            threads: 4,
+        };
+        for ai_moves in 0..Handicap::WEAK_START_MOVES {
+            assert_eq!(
+                Handicap::WeakStart.engine_for_move(ENGINE, ai_moves),
+                Some(weak)
+            );
+        }
+        assert_eq!(
+            Handicap::WeakStart.engine_for_move(ENGINE, Handicap::WEAK_START_MOVES),
+            Some(ENGINE)
+        );
+        // An engine already shallower than the handicap keeps its depth
+        let shallow = EngineSettings { depth: 1, ..ENGINE };
+        assert_eq!(
+            Handicap::WeakStart
+                .engine_for_move(shallow, 0)
+                .map(|engine| engine.depth),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{Board, Evaluation, PositionError, Solver};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// A position where the side to move can force a win in `win_in` of its own moves, and
+/// only one column does so.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub moves: String, // Move string from the empty board, as read by `Board::from_moves`
+    pub win_in: u8,
+}
+
+impl Puzzle {
+    pub fn board(&self) -> Result<Board, PositionError> {
+        Board::from_moves(&self.moves)
+    }
+}
+
+/// Puzzles shipped with the app, easiest first. Each was mined with `generate_puzzles`, so
+/// a search past the win found no second winning first move.
+const CURATED: [(&str, u8); 8] = [
+    ("474435623743", 2),
+    ("4435665174654645551", 2),
+    ("314244331", 3),
+    ("544232535542", 3),
+    ("34444512", 4),
+    ("4753564655", 4),
+    ("443541156", 4),
+    ("6743644313364644", 4),
+];
+
+pub fn curated_puzzles() -> Vec<Puzzle> {
+    CURATED
+        .iter()
+        .map(|&(moves, win_in)| Puzzle {
+            moves: moves.to_string(),
+            win_in,
+        })
+        .collect()
+}
+
+/// Puzzles mined in earlier sessions and which puzzles have been solved.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PuzzleBook {
+    pub mined: Vec<Puzzle>,
+    pub solved: BTreeSet<String>, // Move strings of solved puzzles
+}
+
+impl PuzzleBook {
+    pub const STORAGE_KEY: &'static str = "puzzles";
+
+    /// Curated puzzles followed by mined ones.
+    pub fn puzzles(&self) -> Vec<Puzzle> {
+        let mut puzzles = curated_puzzles();
+        puzzles.extend(self.mined.iter().cloned());
+        puzzles
+    }
+
+    /// Adds newly mined puzzles, skipping any whose position is already in the book or
+    /// repeated in `puzzles`, including mirror images and other move orders.
+    pub fn add_mined(&mut self, puzzles: Vec<Puzzle>) {
+        let mut known: HashSet<u64> = self
+            .puzzles()
+            .iter()
+            .filter_map(|p| p.board().ok())
+            .map(|board| board.canonical_key())
+            .collect();
+        for puzzle in puzzles {
+            let Ok(board) = puzzle.board() else {
+                continue;
+            };
+            if known.insert(board.canonical_key()) {
+                self.mined.push(puzzle);
+            }
+        }
+    }
+
+    pub fn is_solved(&self, puzzle: &Puzzle) -> bool {
+        self.solved.contains(&puzzle.moves)
+    }
+
+    pub fn mark_solved(&mut self, puzzle: &Puzzle) {
+        self.solved.insert(puzzle.moves.clone());
+    }
+}
+
+/// Mines up to `count` puzzles from semi-random self-play, with wins from 2 to `max_win_in`
+/// moves. Games mix the solver's shallow choices with random moves so positions stay
+/// plausible yet varied; the same `seed` gives the same puzzles.
+pub fn generate_puzzles(
+    solver: &mut Solver,
+    count: usize,
+    max_win_in: u8,
+    seed: u64,
+) -> Vec<Puzzle> {
+    const MAX_GAMES: usize = 500;
+    let depth = max_win_in.saturating_mul(2).max(2);
+    let mut rng = seed | 1;
+    let mut seen = HashSet::new();
+    let mut puzzles = Vec::new();
+
+    for _ in 0..MAX_GAMES {
+        if puzzles.len() >= count {
+            break;
+        }
+        let mut board = Board::new();
+        while !board.is_game_over() {
+            if board.moves().len() >= 6
+                && seen.insert(board.canonical_key())
+                && let Some(win_in) = puzzle_win_in(solver, &board, depth, max_win_in)
+            {
+                puzzles.push(Puzzle {
+                    moves: board.move_string(),
+                    win_in,
+                });
+                break; // One puzzle per game keeps the set varied
+            }
+
+            let moves = board.get_valid_moves();
+            let col = if next_random(&mut rng) % 10 < 6 {
+                solver
+                    .find_best_move(&board, 2)
+                    .map_or(moves[0], |result| result.column)
+            } else {
+                moves[next_random(&mut rng) as usize % moves.len()]
+            };
+            board.make_move(col);
+        }
+    }
+    puzzles
+}
+
+/// Extra plies searched to confirm a puzzle has a single solution.
+const UNIQUENESS_MARGIN: u8 = 2;
+
+/// The number of moves to the win if `board` makes a good puzzle: exactly one column wins
+/// by force, in 2 to `max_win_in` moves. A candidate found at `depth` is searched again
+/// `UNIQUENESS_MARGIN` plies deeper, so a second column that only wins past the horizon
+/// rules it out.
+fn puzzle_win_in(solver: &mut Solver, board: &Board, depth: u8, max_win_in: u8) -> Option<u8> {
+    let win_in = single_win(solver, board, depth)?;
+    if !(2..=max_win_in).contains(&win_in) {
+        return None;
+    }
+    (single_win(solver, board, depth.saturating_add(UNIQUENESS_MARGIN)) == Some(win_in))
+        .then_some(win_in)
+}
+
+/// Moves to the win if exactly one column wins by force at `depth`.
+fn single_win(solver: &mut Solver, board: &Board, depth: u8) -> Option<u8> {
+    let mut wins = solver
+        .evaluate_moves(board, depth)
+        .into_iter()
+        .flatten()
+        .filter_map(|eval| match eval {
+            Evaluation::Win(plies) => Some(plies.div_ceil(2)),
+            _ => None,
+        });
+    let win_in = wins.next()?;
+    wins.next().is_none().then_some(win_in)
+}
+
+/// xorshift64: enough randomness for varied self-play without another dependency.
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curated_puzzles_have_one_winning_move() {
+        let mut solver = Solver::new();
+        for puzzle in curated_puzzles() {
+            let board = puzzle.board().unwrap();
+            let depth = puzzle.win_in * 2;
+            assert_eq!(
+                puzzle_win_in(&mut solver, &board, depth, puzzle.win_in),
+                Some(puzzle.win_in),
+                "puzzle {}",
+                puzzle.moves
+            );
+        }
+    }
+
+    #[test]
+    fn generated_puzzles_are_valid_and_repeatable() {
+        let puzzles = generate_puzzles(&mut Solver::new(), 2, 2, 7);
+        assert!(!puzzles.is_empty());
+        let mut solver = Solver::new();
+        for puzzle in &puzzles {
+            let board = puzzle.board().unwrap();
+            assert_eq!(
+                puzzle_win_in(&mut solver, &board, 4, 2),
+                Some(puzzle.win_in)
+            );
+        }
+        assert_eq!(generate_puzzles(&mut Solver::new(), 2, 2, 7), puzzles);
+    }
+
+    #[test]
+    fn puzzle_book_skips_known_puzzles() {
+        let mut book = PuzzleBook::default();
+        let curated = curated_puzzles()[0].clone();
+        let mined = Puzzle {
+            moves: "4444".to_string(),
+            win_in: 3,
+        };
+        book.add_mined(vec![curated.clone(), mined.clone(), mined.clone()]);
+        assert_eq!(book.mined, vec![mined.clone()]);
+        assert_eq!(book.puzzles().len(), CURATED.len() + 1);
+
+        assert!(!book.is_solved(&mined));
+        book.mark_solved(&mined);
+        assert!(book.is_solved(&mined));
+        assert!(!book.is_solved(&curated));
+    }
+
+    #[test]
+    fn puzzle_book_skips_mirrored_and_transposed_positions() {
+        let mut book = PuzzleBook::default();
+        let puzzle = |moves: &str| Puzzle {
+            moves: moves.to_string(),
+            win_in: 2,
+        };
+        // The same position reached in another order, and its mirror image
+        book.add_mined(vec![puzzle("1234"), puzzle("3214"), puzzle("7654")]);
+        assert_eq!(book.mined, vec![puzzle("1234")]);
+        book.add_mined(vec![puzzle("414453265145")]);
+        assert_eq!(book.mined.len(), 1, "mirror of a curated puzzle");
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{Board, GameClock, Handicap, Player};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Search settings used by an engine-controlled side. With a time limit, `depth` is the
+/// deepest iteration the search may reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineSettings {
+    pub depth: u8,
+    pub time_limit_ms: Option<u64>,
+    pub threads: usize,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            depth: 9,
+            time_limit_ms: None,
+            threads: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerKind {
+    Human,
+    Engine(EngineSettings),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    InProgress,
+    Win(Player),
+    WinOnTime(Player), // The other side's clock ran out
+    Draw,
+}
+
+impl GameResult {
+    pub fn from_board(board: &Board) -> Self {
+        match (board.is_game_over(), board.winner()) {
+            (false, _) => GameResult::InProgress,
+            (true, Some(winner)) => GameResult::Win(winner),
+            (true, None) => GameResult::Draw,
+        }
+    }
+}
+
+/// A complete game: who played each side, the columns played in order and how it ended.
+/// Games that didn't start from the empty board keep their starting position, timed games
+/// their clocks and handicap games the handicap given to the human.
+/// Timestamps are seconds since the Unix epoch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub red: PlayerKind,
+    pub yellow: PlayerKind,
+    #[serde(default)]
+    pub start_position: Option<Board>,
+    pub moves: Vec<usize>,
+    pub result: GameResult,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    #[serde(default)]
+    pub clock: Option<GameClock>, // Time left for each side, in timed games
+    #[serde(default)]
+    pub handicap: Handicap,
+}
+
+impl GameRecord {
+    pub fn new(red: PlayerKind, yellow: PlayerKind) -> Self {
+        Self {
+            red,
+            yellow,
+            start_position: None,
+            moves: Vec::new(),
+            result: GameResult::InProgress,
+            started_at: unix_now(),
+            finished_at: None,
+            clock: None,
+            handicap: Handicap::None,
+        }
+    }
+
+    pub fn player_kind(&self, player: Player) -> PlayerKind {
+        match player {
+            Player::Red => self.red,
+            Player::Yellow => self.yellow,
+        }
+    }
+
+    /// Records a move that has just been applied to `board`, finishing the record if the
+    /// move ended the game.
+    pub fn push_move(&mut self, col: usize, board: &Board) {
+        self.moves.push(col);
+        self.result = GameResult::from_board(board);
+        if self.result != GameResult::InProgress {
+            self.finished_at = Some(unix_now());
+        }
+    }
+
+    /// Rebuilds the board by replaying every recorded move from the starting position.
+    /// Returns `None` if any move is illegal.
+    pub fn replay(&self) -> Option<Board> {
+        let mut board = self.start_position.clone().unwrap_or_default();
+        for &col in &self.moves {
+            if !board.make_move(col) {
+                return None;
+            }
+        }
+        Some(board)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ROWS;
+
+    #[test]
+    fn json_round_trips_finished_game() {
+        let mut record = GameRecord::new(PlayerKind::Human, PlayerKind::Engine(Default::default()));
+        let mut board = Board::new();
+        for col in [0, 1, 0, 1, 0, 1, 0] {
+            board.make_move(col);
+            record.push_move(col, &board);
+        }
+        assert_eq!(record.result, GameResult::Win(Player::Red));
+
+        let loaded = GameRecord::from_json(&record.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.red, record.red);
+        assert_eq!(loaded.yellow, record.yellow);
+        assert_eq!(loaded.moves, record.moves);
+        assert_eq!(loaded.result, record.result);
+        assert_eq!(loaded.finished_at, record.finished_at);
+        assert_eq!(loaded.replay().unwrap().winner(), Some(Player::Red));
+    }
+
+    #[test]
+    fn json_keeps_start_position() {
+        let mut start = Board::new();
+        start.make_move(3);
+        let mut record = GameRecord::new(PlayerKind::Human, PlayerKind::Human);
+        record.start_position = Some(start);
+        record.moves = vec![3, 2];
+
+        let loaded = GameRecord::from_json(&record.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.start_position.as_ref().unwrap().moves(), &[3]);
+        assert_eq!(loaded.replay().unwrap().moves(), &[3, 3, 2]);
+    }
+
+    #[test]
+    fn replay_rejects_illegal_moves() {
+        let mut record = GameRecord::new(PlayerKind::Human, PlayerKind::Human);
+        record.moves = vec![0; ROWS + 1];
+        assert!(record.replay().is_none());
+    }
+}
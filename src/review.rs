@@ -0,0 +1,191 @@
+use crate::{Board, Evaluation, Player, PositionAnalysis, Solver};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MoveQuality {
+    Best,
+    Inaccuracy, // Same proven outcome, but a slower win or a faster loss
+    Blunder,    // Worse proven outcome: a win thrown away, or a loss walked into
+}
+
+/// Verdict on one move of a game, compared with the best alternative the solver found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveReview {
+    pub player: Player,
+    pub column: usize,
+    pub played: Evaluation,
+    pub best_column: usize,
+    pub best: Evaluation,
+    pub quality: MoveQuality,
+}
+
+impl MoveReview {
+    /// Judges `column`, played by `player`, against the analysis of the position before it.
+    /// Only proven results count against a move: heuristic scores at a fixed depth are too
+    /// noisy to call a move worse, and a move that secures a draw is never marked down for
+    /// a heuristic score above it. Returns `None` if the analysis has no evaluation for that
+    /// column.
+    pub fn classify(analysis: &PositionAnalysis, player: Player, column: usize) -> Option<Self> {
+        let played = analysis.columns.get(column).copied().flatten()?;
+        let best_column = analysis.best_column()?;
+        let best = analysis.columns[best_column]?;
+
+        let quality = match (played, best) {
+            _ if outcome_class(played) < outcome_class(best) => MoveQuality::Blunder,
+            (Evaluation::Win(played), Evaluation::Win(best)) if played > best => {
+                MoveQuality::Inaccuracy
+            }
+            (Evaluation::Loss(played), Evaluation::Loss(best)) if played < best => {
+                MoveQuality::Inaccuracy
+            }
+            _ => MoveQuality::Best,
+        };
+
+        Some(Self {
+            player,
+            column,
+            played,
+            // A move as good as the best is its own best alternative
+            best_column: if quality == MoveQuality::Best {
+                column
+            } else {
+                best_column
+            },
+            best,
+            quality,
+        })
+    }
+}
+
+/// 2 for a forced win, 1 for a draw or unresolved position, 0 for a forced loss. Draws and
+/// heuristic scores share a class, since a heuristic score proves nothing either way.
+fn outcome_class(evaluation: Evaluation) -> u8 {
+    match evaluation {
+        Evaluation::Win(_) => 2,
+        Evaluation::Draw | Evaluation::Heuristic(_) => 1,
+        Evaluation::Loss(_) => 0,
+    }
+}
+
+/// Re-solves every position of a game played from `start` and reviews each move. Stops at
+/// the first illegal move.
+pub fn review_game(
+    start: &Board,
+    moves: &[usize],
+    solver: &mut Solver,
+    depth: u8,
+) -> Vec<MoveReview> {
+    let mut board = start.clone();
+    let mut reviews = Vec::with_capacity(moves.len());
+    for &col in moves {
+        let analysis = PositionAnalysis {
+            key: board.key(),
+            depth,
+            columns: solver.evaluate_moves(&board, depth),
+        };
+        let player = board.current_player();
+        if !board.make_move(col) {
+            break;
+        }
+        if let Some(review) = MoveReview::classify(&analysis, player, col) {
+            reviews.push(review);
+        }
+    }
+    reviews
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLS;
+    use Evaluation::{Draw, Heuristic, Loss, Win};
+
+    fn analysis(columns: [Option<Evaluation>; COLS]) -> PositionAnalysis {
+        PositionAnalysis {
+            key: 0,
+            depth: 8,
+            columns,
+        }
+    }
+
+    fn quality(columns: [Option<Evaluation>; COLS], column: usize) -> MoveQuality {
+        MoveReview::classify(&analysis(columns), Player::Red, column)
+            .unwrap()
+            .quality
+    }
+
+    #[test]
+    fn throwing_away_a_proven_result_is_a_blunder() {
+        let columns = [
+            Some(Win(3)),
+            Some(Draw),
+            Some(Loss(2)),
+            None,
+            None,
+            None,
+            None,
+        ];
+        assert_eq!(quality(columns, 0), MoveQuality::Best);
+        assert_eq!(quality(columns, 1), MoveQuality::Blunder);
+        assert_eq!(quality(columns, 2), MoveQuality::Blunder);
+    }
+
+    #[test]
+    fn slower_win_or_faster_loss_is_an_inaccuracy() {
+        let wins = [Some(Win(3)), Some(Win(7)), None, None, None, None, None];
+        assert_eq!(quality(wins, 1), MoveQuality::Inaccuracy);
+        let losses = [Some(Loss(8)), Some(Loss(2)), None, None, None, None, None];
+        assert_eq!(quality(losses, 1), MoveQuality::Inaccuracy);
+    }
+
+    #[test]
+    fn heuristic_scores_never_mark_a_move_down() {
+        let columns = [
+            Some(Heuristic(40)),
+            Some(Heuristic(-40)),
+            Some(Draw),
+            None,
+            None,
+            None,
+            None,
+        ];
+        assert_eq!(quality(columns, 1), MoveQuality::Best);
+        assert_eq!(quality(columns, 2), MoveQuality::Best);
+    }
+
+    #[test]
+    fn review_points_to_the_better_column() {
+        let columns = [Some(Draw), None, None, Some(Win(1)), None, None, None];
+        let review = MoveReview::classify(&analysis(columns), Player::Red, 0).unwrap();
+        assert_eq!(review.best_column, 3);
+        assert_eq!(review.played, Draw);
+        let best = MoveReview::classify(&analysis(columns), Player::Red, 3).unwrap();
+        assert_eq!(best.best_column, 3);
+        assert!(MoveReview::classify(&analysis(columns), Player::Red, 1).is_none());
+    }
+
+    #[test]
+    fn review_game_finds_a_missed_block() {
+        // Red threatens four in the first column; Yellow looks elsewhere and Red wins
+        let start = Board::from_moves("12121").unwrap();
+        let reviews = review_game(&start, &[6, 0], &mut Solver::new(), 4);
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0].player, Player::Yellow);
+        assert_eq!(reviews[0].quality, MoveQuality::Blunder);
+        assert_eq!(reviews[0].best_column, 0);
+        assert_eq!(reviews[1].quality, MoveQuality::Best);
+        assert_eq!(reviews[1].played, Win(1));
+    }
+
+    #[test]
+    fn unproven_wins_do_not_mark_moves_down() {
+        // The solver once memoized a cut-off search here as a win for the fifth column, so
+        // every other Yellow move was reviewed as a blunder; nothing is forced at depth 4
+        let start = Board::from_moves("22123354").unwrap();
+        let reviews = review_game(&start, &[0, 5], &mut Solver::new(), 4);
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[1].player, Player::Yellow);
+        assert!(matches!(reviews[1].best, Heuristic(_)));
+        assert_eq!(reviews[1].quality, MoveQuality::Best);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{EngineSettings, TimeControl};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/// RGB colors of the board and each player's discs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Palette {
+    pub red: [u8; 3],
+    pub yellow: [u8; 3],
+    pub board: [u8; 3],
+}
+
+impl Palette {
+    pub const CLASSIC: Palette = Palette {
+        red: [220, 50, 50],
+        yellow: [255, 215, 50],
+        board: [41, 98, 255],
+    };
+
+    /// Vermillion and yellow on blue from the Okabe-Ito palette, which stay apart under the
+    /// common forms of color blindness.
+    pub const COLORBLIND: Palette = Palette {
+        red: [213, 94, 0],
+        yellow: [240, 228, 66],
+        board: [0, 114, 178],
+    };
+
+    pub const HIGH_CONTRAST: Palette = Palette {
+        red: [200, 0, 0],
+        yellow: [255, 220, 0],
+        board: [25, 25, 25],
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaletteChoice {
+    Classic,
+    Colorblind,
+    HighContrast,
+    Custom, // Uses `Settings::custom_palette`
+}
+
+/// User preferences set from the settings window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub engine: EngineSettings, // Used by the AI in Human vs AI games and by analysis
+    pub ai_delay_ms: u64,       // Pause before the AI moves, so its moves are easy to follow
+    pub theme: Theme,
+    pub palette: PaletteChoice,
+    pub custom_palette: Palette, // Kept while another palette is selected
+    pub disc_symbols: bool,      // Mark discs with a shape as well as a color
+    pub time_control: Option<TimeControl>, // Clocks for new games; None plays untimed
+    pub profile: String,         // Whose statistics games against the AI count for
+    pub show_hints: bool,
+}
+
+impl Settings {
+    pub const STORAGE_KEY: &'static str = "settings";
+
+    /// Colors of the selected palette.
+    pub fn palette(&self) -> Palette {
+        match self.palette {
+            PaletteChoice::Classic => Palette::CLASSIC,
+            PaletteChoice::Colorblind => Palette::COLORBLIND,
+            PaletteChoice::HighContrast => Palette::HIGH_CONTRAST,
+            PaletteChoice::Custom => self.custom_palette,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            engine: EngineSettings::default(),
+            ai_delay_ms: 300,
+            theme: Theme::Light,
+            palette: PaletteChoice::Classic,
+            custom_palette: Palette::CLASSIC,
+            disc_symbols: false,
+            time_control: None,
+            profile: "Player".to_string(),
+            show_hints: true,
+        }
+    }
+}
@@ -1,20 +1,114 @@
-use crate::{Board, COLS, Cell, Player, ROWS};
+use crate::{Board, COLS, Cell, EngineSettings, Player, PositionAnalysis, ROWS};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy)]
 pub struct MoveResult {
     pub column: usize,
     pub moves_to_win: Option<u8>, // None if no forced win, Some(n) if win in n moves
+    pub evaluation: Evaluation,   // Score of the chosen move for the side making it
 }
 
+/// Solver verdict for a move, from the point of view of the player making it.
+/// Win and loss distances are in plies, counting the move itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Evaluation {
+    Win(u8),
+    Loss(u8),
+    Draw,
+    Heuristic(i32), // Positive favors the player making the move
+}
+
+impl Evaluation {
+    fn from_result(result: EvalResult, player: Player) -> Self {
+        let score = match player {
+            Player::Red => result.score,
+            Player::Yellow => -result.score,
+        };
+        let plies = result.moves_to_outcome.map_or(1, |m| m.saturating_add(1));
+        if score >= WIN_SCORE {
+            Evaluation::Win(plies)
+        } else if score <= -WIN_SCORE {
+            Evaluation::Loss(plies)
+        } else if score == 0 && result.moves_to_outcome.is_some() {
+            Evaluation::Draw
+        } else {
+            Evaluation::Heuristic(score)
+        }
+    }
+
+    /// Orders evaluations from the mover's point of view: faster wins rank highest, slower
+    /// losses rank above faster ones.
+    pub fn rank(self) -> i64 {
+        match self {
+            Evaluation::Win(plies) => 2_000_000 - plies as i64,
+            Evaluation::Loss(plies) => -2_000_000 + plies as i64,
+            Evaluation::Draw => 0,
+            Evaluation::Heuristic(score) => score as i64,
+        }
+    }
+
+    /// How strongly the evaluation favors the mover, from -1.0 (forced loss) to 1.0 (forced
+    /// win). Heuristic scores are squashed to stay clear of the proven results.
+    pub fn advantage(self) -> f32 {
+        match self {
+            Evaluation::Win(_) => 1.0,
+            Evaluation::Loss(_) => -1.0,
+            Evaluation::Draw => 0.0,
+            Evaluation::Heuristic(score) => 0.9 * (score as f32 / 60.0).tanh(),
+        }
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            // The mover's own moves to the win, and the opponent's moves to the loss
+            Evaluation::Win(plies) => write!(f, "Win in {}", plies.div_ceil(2)),
+            Evaluation::Loss(plies) => write!(f, "Loss in {}", plies / 2),
+            Evaluation::Draw => write!(f, "Draw"),
+            Evaluation::Heuristic(score) => write!(f, "{score:+}"),
+        }
+    }
+}
+
+const WIN_SCORE: i32 = 1000;
+
+/// Memo entries kept before the memo is cleared, so a long-lived solver stays within a
+/// bounded amount of memory (a few dozen bytes per entry).
+const MEMO_LIMIT: usize = 1 << 21;
+
 #[derive(Debug, Clone, Copy)]
 struct EvalResult {
     score: i32,
     moves_to_outcome: Option<u8>, // Moves until win/loss (None if draw or uncertain)
 }
 
+/// How a memoized score relates to the true value. Searches cut off by alpha-beta only
+/// bound the value, so they can be reused only when the bound decides the new window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower, // The true score is at least this
+    Upper, // The true score is at most this
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MemoEntry {
+    result: EvalResult,
+    bound: Bound,
+}
+
 pub struct Solver {
-    memo: HashMap<String, EvalResult>,
+    memo: HashMap<(u64, u8), MemoEntry>,
+    deadline: Option<Instant>,     // Searches past this are abandoned
+    stop: Option<Arc<AtomicBool>>, // Searches are abandoned once this is set
+    aborted: bool,
 }
 
 impl Default for Solver {
@@ -27,10 +121,56 @@ impl Solver {
     pub fn new() -> Self {
         Self {
             memo: HashMap::new(),
+            deadline: None,
+            stop: None,
+            aborted: false,
         }
     }
 
+    /// Makes every later search give up once `stop` is set, e.g. from another thread when
+    /// its result is no longer wanted. A stopped `find_best_move` or `search` returns `None`
+    /// and a stopped `evaluate_moves` evaluates no column.
+    pub fn set_stop_flag(&mut self, stop: Option<Arc<AtomicBool>>) {
+        self.stop = stop;
+    }
+
     pub fn find_best_move(&mut self, board: &Board, depth: u8) -> Option<MoveResult> {
+        self.aborted = false;
+        self.search_depth(board, depth, 1)
+    }
+
+    /// Finds the best move within `settings`: a fixed-depth search, or with a time limit,
+    /// iterative deepening up to `settings.depth` that returns the deepest search finished
+    /// in time (the depth-1 search always completes). Root moves are split across
+    /// `settings.threads` threads.
+    pub fn search(&mut self, board: &Board, settings: &EngineSettings) -> Option<MoveResult> {
+        self.aborted = false;
+        let threads = settings.threads.max(1);
+        let Some(time_limit) = settings.time_limit_ms.map(Duration::from_millis) else {
+            return self.search_depth(board, settings.depth, threads);
+        };
+
+        let deadline = Instant::now() + time_limit;
+        let mut best = None;
+        for depth in 1..=settings.depth.max(1) {
+            self.deadline = (depth > 1).then_some(deadline);
+            let result = self.search_depth(board, depth, threads);
+            let aborted = self.aborted;
+            self.deadline = None;
+            self.aborted = false;
+            if aborted {
+                break;
+            }
+            best = result;
+            // A forced win can't be improved on by searching deeper
+            if Instant::now() >= deadline || best.is_some_and(|r| r.moves_to_win.is_some()) {
+                break;
+            }
+        }
+        best
+    }
+
+    fn search_depth(&mut self, board: &Board, depth: u8, threads: usize) -> Option<MoveResult> {
         if board.is_game_over() {
             return None;
         }
@@ -40,34 +180,35 @@ impl Solver {
             return None;
         }
 
+        // Search on a scratch copy, applying and taking back moves in place
+        let mut board = board.clone();
+
         // Immediate winning move
-        if let Some(winning_col) = self.find_immediate_win(board) {
+        if let Some(winning_col) = self.find_immediate_win(&mut board) {
             return Some(MoveResult {
                 column: winning_col,
                 moves_to_win: Some(1),
+                evaluation: Evaluation::Win(1),
             });
         }
 
         // Prefer center-first move ordering to improve pruning and play strength
         self.order_moves_center_out(&mut valid_moves);
 
-        let mut best_move = valid_moves[0];
-        let mut best_moves_to_win: Option<u8> = None;
-
         // Determine if AI should maximize or minimize based on current player
         // Red maximizes (seeks positive scores), Yellow minimizes (seeks negative scores)
-        let ai_maximizes = board.current_player() == Player::Red;
-        let mut best_score = if ai_maximizes { i32::MIN } else { i32::MAX };
+        let player = board.current_player();
+        let ai_maximizes = player == Player::Red;
 
         // Root-level losing move avoidance: avoid moves that immediately allow opponent to win
         // Prefer among non-losing moves if any exist
         let mut non_losing_candidates: Vec<usize> = Vec::new();
 
         for &col in &valid_moves {
-            let mut new_board = board.clone();
-            new_board.make_move(col);
+            board.make_move(col);
             // After making the move, it's the opponent's turn, so flip the maximizing flag
-            let opponent_has_mate_in_1 = self.find_immediate_win(&new_board).is_some();
+            let opponent_has_mate_in_1 = self.find_immediate_win(&mut board).is_some();
+            board.undo_move();
             if !opponent_has_mate_in_1 {
                 non_losing_candidates.push(col);
             }
@@ -79,42 +220,173 @@ impl Solver {
             valid_moves.clone()
         };
 
-        for &col in &search_space {
-            let mut new_board = board.clone();
-            new_board.make_move(col);
-            let eval_result = self.minimax(&new_board, depth, i32::MIN, i32::MAX, !ai_maximizes);
-
-            let is_better = if ai_maximizes {
-                eval_result.score > best_score
-                    || (eval_result.score == best_score
-                        && self.is_faster_win(
-                            eval_result.moves_to_outcome,
-                            best_moves_to_win,
-                            ai_maximizes,
-                        ))
-            } else {
-                eval_result.score < best_score
-                    || (eval_result.score == best_score
-                        && self.is_faster_win(
-                            eval_result.moves_to_outcome,
-                            best_moves_to_win,
-                            ai_maximizes,
-                        ))
+        let evaluated = if threads > 1 && search_space.len() > 1 {
+            self.evaluate_root_parallel(&board, &search_space, depth, threads)
+        } else {
+            search_space
+                .iter()
+                .map(|&col| {
+                    board.make_move(col);
+                    let eval_result =
+                        self.minimax(&mut board, depth, i32::MIN, i32::MAX, !ai_maximizes);
+                    board.undo_move();
+                    (col, eval_result)
+                })
+                .collect()
+        };
+        if self.aborted {
+            return None;
+        }
+
+        let mut best_move = search_space[0];
+        let mut best_result: Option<EvalResult> = None;
+        for (col, eval_result) in evaluated {
+            let is_better = match best_result {
+                None => true,
+                Some(best) if ai_maximizes => {
+                    eval_result.score > best.score
+                        || (eval_result.score == best.score
+                            && self.is_faster_win(
+                                eval_result.moves_to_outcome,
+                                best.moves_to_outcome,
+                                ai_maximizes,
+                            ))
+                }
+                Some(best) => {
+                    eval_result.score < best.score
+                        || (eval_result.score == best.score
+                            && self.is_faster_win(
+                                eval_result.moves_to_outcome,
+                                best.moves_to_outcome,
+                                ai_maximizes,
+                            ))
+                }
             };
 
             if is_better {
-                best_score = eval_result.score;
                 best_move = col;
-                best_moves_to_win = eval_result.moves_to_outcome.map(|m| m + 1); // +1 for the move we're about to make
+                best_result = Some(eval_result);
             }
         }
 
+        let evaluation = Evaluation::from_result(best_result?, player);
+        // Only a proven win counts; draws and losses also carry a distance
+        let moves_to_win = match evaluation {
+            Evaluation::Win(plies) => Some(plies),
+            _ => None,
+        };
         Some(MoveResult {
             column: best_move,
-            moves_to_win: best_moves_to_win,
+            moves_to_win,
+            evaluation,
         })
     }
 
+    /// Searches root moves on `threads` threads, each with its own memo. The root already
+    /// uses a full window per move, so results match a single-threaded search.
+    fn evaluate_root_parallel(
+        &mut self,
+        board: &Board,
+        moves: &[usize],
+        depth: u8,
+        threads: usize,
+    ) -> Vec<(usize, EvalResult)> {
+        let maximizing = board.current_player() != Player::Red;
+        let deadline = self.deadline;
+        let stop = &self.stop;
+        let outcomes: Vec<(Vec<(usize, EvalResult)>, bool)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads.min(moves.len()))
+                .map(|worker| {
+                    let mut board = board.clone();
+                    scope.spawn(move || {
+                        let mut solver = Solver {
+                            deadline,
+                            stop: stop.clone(),
+                            ..Solver::new()
+                        };
+                        let evaluated: Vec<_> = moves
+                            .iter()
+                            .skip(worker)
+                            .step_by(threads)
+                            .map(|&col| {
+                                board.make_move(col);
+                                let eval_result = solver.minimax(
+                                    &mut board,
+                                    depth,
+                                    i32::MIN,
+                                    i32::MAX,
+                                    maximizing,
+                                );
+                                board.undo_move();
+                                (col, eval_result)
+                            })
+                            .collect();
+                        (evaluated, solver.aborted)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("search thread panicked"))
+                .collect()
+        });
+
+        let mut evaluated = Vec::with_capacity(moves.len());
+        for (results, aborted) in outcomes {
+            self.aborted |= aborted;
+            evaluated.extend(results);
+        }
+        // Restore the center-out order so ties resolve as in a single-threaded search
+        evaluated.sort_by_key(|&(col, _)| moves.iter().position(|&m| m == col));
+        evaluated
+    }
+
+    /// The line both sides are expected to play from `board`: the best column by
+    /// `evaluate_moves` at `depth`, then the best reply by `evaluate_moves` a ply shallower,
+    /// and so on until the depth runs out or the game ends. Each reply keeps the score of
+    /// the move before it, so the line agrees with the column scores at `depth`.
+    pub fn principal_variation(&mut self, board: &Board, depth: u8) -> Vec<usize> {
+        let mut board = board.clone();
+        let mut line = Vec::new();
+        for remaining in (0..=depth).rev() {
+            let analysis = PositionAnalysis {
+                key: board.key(),
+                depth: remaining,
+                columns: self.evaluate_moves(&board, remaining),
+            };
+            let Some(col) = analysis.best_column() else {
+                break;
+            };
+            board.make_move(col);
+            line.push(col);
+        }
+        line
+    }
+
+    /// Evaluates every column for the side to move; `None` for full columns. Each move is
+    /// searched to the same depth `find_best_move` uses.
+    pub fn evaluate_moves(&mut self, board: &Board, depth: u8) -> [Option<Evaluation>; COLS] {
+        let mut evaluations = [None; COLS];
+        self.aborted = false;
+        if board.is_game_over() {
+            return evaluations;
+        }
+
+        let mut board = board.clone();
+        let player = board.current_player();
+        for col in board.get_valid_moves() {
+            board.make_move(col);
+            let maximizing = board.current_player() == Player::Red;
+            let eval_result = self.minimax(&mut board, depth, i32::MIN, i32::MAX, maximizing);
+            board.undo_move();
+            evaluations[col] = Some(Evaluation::from_result(eval_result, player));
+        }
+        if self.aborted {
+            return [None; COLS];
+        }
+        evaluations
+    }
+
     fn is_faster_win(
         &self,
         new_moves: Option<u8>,
@@ -136,102 +408,121 @@ impl Solver {
 
     fn minimax(
         &mut self,
-        board: &Board,
+        board: &mut Board,
         depth: u8,
         mut alpha: i32,
         mut beta: i32,
         maximizing: bool,
     ) -> EvalResult {
+        let expired = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        let stopped = self
+            .stop
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed));
+        if self.aborted || expired || stopped {
+            // The caller discards aborted searches, so the value doesn't matter
+            self.aborted = true;
+            return EvalResult {
+                score: 0,
+                moves_to_outcome: None,
+            };
+        }
+
         if depth == 0 || board.is_game_over() {
             return self.evaluate_board_with_depth(board, depth);
         }
 
-        let board_key = format!("{}_{}", self.board_to_string(board), depth);
-        if let Some(&cached_result) = self.memo.get(&board_key) {
-            return cached_result;
+        // Mirror images share an entry; scores are invariant under left-right mirroring
+        let board_key = (board.canonical_key(), depth);
+        if let Some(entry) = self.memo.get(&board_key) {
+            let score = entry.result.score;
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+            if usable {
+                return entry.result;
+            }
         }
+        let (alpha_in, beta_in) = (alpha, beta);
 
         let mut valid_moves = board.get_valid_moves();
         self.order_moves_center_out(&mut valid_moves);
 
-        if maximizing {
-            let mut best_result = EvalResult {
-                score: i32::MIN,
-                moves_to_outcome: None,
-            };
+        let mut best_result = EvalResult {
+            score: if maximizing { i32::MIN } else { i32::MAX },
+            moves_to_outcome: None,
+        };
+        for &col in &valid_moves {
+            board.make_move(col);
+            let eval_result = self.minimax(board, depth - 1, alpha, beta, !maximizing);
+            board.undo_move();
 
-            for &col in &valid_moves {
-                let mut new_board = board.clone();
-                new_board.make_move(col);
-                let eval_result = self.minimax(&new_board, depth - 1, alpha, beta, false);
-
-                let is_better = eval_result.score > best_result.score
-                    || (eval_result.score == best_result.score
-                        && self.is_faster_win(
-                            eval_result.moves_to_outcome,
-                            best_result.moves_to_outcome,
-                            true,
-                        ));
-
-                if is_better {
-                    best_result = EvalResult {
-                        score: eval_result.score,
-                        moves_to_outcome: eval_result.moves_to_outcome.map(|m| m + 1),
-                    };
-                }
+            let is_better = if maximizing {
+                eval_result.score > best_result.score
+            } else {
+                eval_result.score < best_result.score
+            } || (eval_result.score == best_result.score
+                && self.is_faster_win(
+                    eval_result.moves_to_outcome,
+                    best_result.moves_to_outcome,
+                    maximizing,
+                ));
 
-                alpha = alpha.max(eval_result.score);
-                if beta <= alpha {
-                    break; // Alpha-beta pruning
-                }
+            if is_better {
+                best_result = EvalResult {
+                    score: eval_result.score,
+                    moves_to_outcome: eval_result.moves_to_outcome.map(|m| m + 1),
+                };
             }
-            self.memo.insert(board_key, best_result);
-            best_result
-        } else {
-            let mut best_result = EvalResult {
-                score: i32::MAX,
-                moves_to_outcome: None,
-            };
-
-            for &col in &valid_moves {
-                let mut new_board = board.clone();
-                new_board.make_move(col);
-                let eval_result = self.minimax(&new_board, depth - 1, alpha, beta, true);
-
-                let is_better = eval_result.score < best_result.score
-                    || (eval_result.score == best_result.score
-                        && self.is_faster_win(
-                            eval_result.moves_to_outcome,
-                            best_result.moves_to_outcome,
-                            false,
-                        ));
-
-                if is_better {
-                    best_result = EvalResult {
-                        score: eval_result.score,
-                        moves_to_outcome: eval_result.moves_to_outcome.map(|m| m + 1),
-                    };
-                }
 
+            if maximizing {
+                alpha = alpha.max(eval_result.score);
+            } else {
                 beta = beta.min(eval_result.score);
-                if beta <= alpha {
-                    break; // Alpha-beta pruning
-                }
             }
-            self.memo.insert(board_key, best_result);
-            best_result
+            if beta <= alpha {
+                break; // Alpha-beta pruning
+            }
+        }
+
+        if !self.aborted {
+            // A score outside the window it was searched with is only a bound
+            let bound = if best_result.score <= alpha_in {
+                Bound::Upper
+            } else if best_result.score >= beta_in {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            if self.memo.len() >= MEMO_LIMIT {
+                self.memo.clear();
+            }
+            self.memo.insert(
+                board_key,
+                MemoEntry {
+                    result: best_result,
+                    bound,
+                },
+            );
         }
+        best_result
     }
 
-    fn evaluate_board_with_depth(&self, board: &Board, _depth: u8) -> EvalResult {
+    fn evaluate_board_with_depth(&self, board: &Board, depth: u8) -> EvalResult {
+        // Wins found with more depth remaining are nearer the root, so they score higher;
+        // alpha-beta then prefers faster wins and slower losses on its own
         if let Some(winner) = board.winner() {
             return match winner {
                 Player::Red => EvalResult {
-                    score: 1000,
+                    score: WIN_SCORE + depth as i32,
                     moves_to_outcome: Some(0),
                 },
                 Player::Yellow => EvalResult {
-                    score: -1000,
+                    score: -WIN_SCORE - depth as i32,
                     moves_to_outcome: Some(0),
                 },
             };
@@ -324,30 +615,13 @@ impl Solver {
         0
     }
 
-    fn board_to_string(&self, board: &Board) -> String {
-        let mut result = String::new();
-        for row in 0..ROWS {
-            for col in 0..COLS {
-                match board.get_cell(row, col) {
-                    Cell::Empty => result.push('0'),
-                    Cell::Occupied(Player::Red) => result.push('1'),
-                    Cell::Occupied(Player::Yellow) => result.push('2'),
-                }
-            }
-        }
-        result.push(match board.current_player() {
-            Player::Red => '1',
-            Player::Yellow => '2',
-        });
-        result
-    }
-
-    fn find_immediate_win(&self, board: &Board) -> Option<usize> {
+    fn find_immediate_win(&self, board: &mut Board) -> Option<usize> {
         let me = board.current_player();
         for col in board.get_valid_moves() {
-            let mut nb = board.clone();
-            nb.make_move(col);
-            if nb.is_game_over() && nb.winner() == Some(me) {
+            board.make_move(col);
+            let wins = board.is_game_over() && board.winner() == Some(me);
+            board.undo_move();
+            if wins {
                 return Some(col);
             }
         }
@@ -359,3 +633,162 @@ impl Solver {
         moves.sort_by_key(|&c| (c as i32 - center).abs());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimax without pruning or memo, with the same leaf scores and tie-breaks.
+    fn plain_minimax(
+        solver: &Solver,
+        board: &mut Board,
+        depth: u8,
+        maximizing: bool,
+    ) -> EvalResult {
+        if depth == 0 || board.is_game_over() {
+            return solver.evaluate_board_with_depth(board, depth);
+        }
+        let mut valid_moves = board.get_valid_moves();
+        solver.order_moves_center_out(&mut valid_moves);
+        let mut best: Option<EvalResult> = None;
+        for col in valid_moves {
+            board.make_move(col);
+            let result = plain_minimax(solver, board, depth - 1, !maximizing);
+            board.undo_move();
+            let is_better = best.is_none_or(|best| {
+                if maximizing {
+                    result.score > best.score
+                } else {
+                    result.score < best.score
+                }
+            });
+            if is_better {
+                best = Some(EvalResult {
+                    score: result.score,
+                    moves_to_outcome: result.moves_to_outcome.map(|m| m + 1),
+                });
+            }
+        }
+        best.unwrap()
+    }
+
+    fn plain_evaluate_moves(board: &Board, depth: u8) -> [Option<Evaluation>; COLS] {
+        let solver = Solver::new();
+        let mut board = board.clone();
+        let player = board.current_player();
+        let mut evaluations = [None; COLS];
+        for col in board.get_valid_moves() {
+            board.make_move(col);
+            let maximizing = board.current_player() == Player::Red;
+            let result = plain_minimax(&solver, &mut board, depth, maximizing);
+            board.undo_move();
+            evaluations[col] = Some(Evaluation::from_result(result, player));
+        }
+        evaluations
+    }
+
+    /// Random positions that are still in play, from a fixed seed.
+    fn random_positions(count: usize, seed: u64) -> Vec<Board> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize
+        };
+        let mut positions = Vec::new();
+        while positions.len() < count {
+            let mut board = Board::new();
+            let plies = 6 + next() % 14;
+            while board.moves().len() < plies && !board.is_game_over() {
+                let moves = board.get_valid_moves();
+                board.make_move(moves[next() % moves.len()]);
+            }
+            if !board.is_game_over() {
+                positions.push(board);
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn evaluate_moves_matches_plain_minimax() {
+        for depth in [3, 4] {
+            // One solver throughout, so memo entries are reused across positions
+            let mut solver = Solver::new();
+            for board in random_positions(30, u64::from(depth)) {
+                assert_eq!(
+                    solver.evaluate_moves(&board, depth),
+                    plain_evaluate_moves(&board, depth),
+                    "position {} at depth {depth}",
+                    board.move_string()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cut_off_searches_are_not_reported_as_proven() {
+        // A memoized cut-off once reported column 5 here as "Win in 3" at every depth
+        let board = Board::from_moves("221233541").unwrap();
+        let depth = 4;
+        let evaluations = Solver::new().evaluate_moves(&board, depth);
+        assert_eq!(evaluations, plain_evaluate_moves(&board, depth));
+        assert!(matches!(evaluations[4], Some(Evaluation::Heuristic(_))));
+    }
+
+    #[test]
+    fn principal_variation_follows_the_column_scores() {
+        let mut solver = Solver::new();
+        for board in random_positions(10, 9) {
+            let depth = 4;
+            let line = solver.principal_variation(&board, depth);
+            let mut position = board.clone();
+            let mut expected = None;
+            for (ply, &col) in line.iter().enumerate() {
+                let columns = solver.evaluate_moves(&position, depth - ply as u8);
+                let analysis = PositionAnalysis {
+                    key: position.key(),
+                    depth: depth - ply as u8,
+                    columns,
+                };
+                assert_eq!(analysis.best_column(), Some(col));
+                let eval = columns[col].unwrap();
+                if let Some(expected) = expected {
+                    assert_eq!(eval, expected, "ply {ply} of {}", board.move_string());
+                }
+                // The reply carries the same score, seen from the other side
+                expected = Some(match eval {
+                    Evaluation::Win(plies) => Evaluation::Loss(plies - 1),
+                    Evaluation::Loss(plies) => Evaluation::Win(plies - 1),
+                    Evaluation::Draw => Evaluation::Draw,
+                    Evaluation::Heuristic(score) => Evaluation::Heuristic(-score),
+                });
+                position.make_move(col);
+            }
+            assert!(line.len() == usize::from(depth) + 1 || position.is_game_over());
+        }
+    }
+
+    #[test]
+    fn stopped_searches_return_nothing() {
+        let board = Board::from_moves("4453").unwrap();
+        let mut solver = Solver::new();
+        let stop = Arc::new(AtomicBool::new(true));
+        solver.set_stop_flag(Some(Arc::clone(&stop)));
+        assert!(solver.find_best_move(&board, 6).is_none());
+        assert_eq!(solver.evaluate_moves(&board, 6), [None; COLS]);
+        let settings = EngineSettings {
+            threads: 2,
+            ..EngineSettings::default()
+        };
+        assert!(solver.search(&board, &settings).is_none());
+
+        // The memo holds nothing from the stopped searches
+        stop.store(false, Ordering::Relaxed);
+        assert_eq!(
+            solver.evaluate_moves(&board, 3),
+            plain_evaluate_moves(&board, 3)
+        );
+    }
+}
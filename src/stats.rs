@@ -0,0 +1,281 @@
+use crate::{EngineSettings, GameRecord, GameResult, Handicap, Player, PlayerKind, TimeControl};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Results of a set of finished games, from one player's point of view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub total_plies: u64, // Summed over every game, for the average length
+}
+
+impl Tally {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Share of games won, from 0.0 to 1.0, or `None` before the first game.
+    pub fn win_rate(&self) -> Option<f32> {
+        let games = self.games();
+        (games > 0).then(|| self.wins as f32 / games as f32)
+    }
+
+    /// Average number of plies per game, or `None` before the first game.
+    pub fn average_plies(&self) -> Option<f32> {
+        let games = self.games();
+        (games > 0).then(|| self.total_plies as f32 / games as f32)
+    }
+
+    fn add(&mut self, outcome: Outcome, plies: usize) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+        }
+        self.total_plies += plies as u64;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// One profile's results in games against the AI.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStats {
+    pub overall: Tally,
+    pub moving_first: Tally,
+    pub moving_second: Tally,
+    pub by_engine: BTreeMap<String, Tally>, // Keyed by `engine_label` of the AI's settings
+}
+
+/// Describes how the AI played a game: its depth, any time limit per move, and the clock
+/// it also had to keep to, e.g. "up to depth 12, 500 ms per move, 3+2 clock". Threads only
+/// count with a time limit, where they change how deep the search gets.
+fn engine_label(engine: &EngineSettings, clock: Option<TimeControl>) -> String {
+    let mut label = match engine.time_limit_ms {
+        Some(limit) => format!("up to depth {}, {limit} ms per move", engine.depth),
+        None => format!("depth {}", engine.depth),
+    };
+    if engine.time_limit_ms.is_some() && engine.threads > 1 {
+        label += &format!(", {} threads", engine.threads);
+    }
+    if let Some(control) = clock {
+        label += &format!(
+            ", {}+{} clock",
+            control.initial_ms / 60_000,
+            control.increment_ms / 1000
+        );
+    }
+    label
+}
+
+/// Win/loss statistics for every profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub profiles: BTreeMap<String, ProfileStats>,
+}
+
+impl Statistics {
+    pub const STORAGE_KEY: &'static str = "statistics";
+
+    pub fn profile(&self, name: &str) -> Option<&ProfileStats> {
+        self.profiles.get(name)
+    }
+
+    /// Counts a finished Human vs AI game for `profile`, the human side. Returns whether it
+    /// was counted: unfinished games, handicap games and games without exactly one human are
+    /// skipped.
+    pub fn record_game(&mut self, profile: &str, record: &GameRecord) -> bool {
+        if record.handicap != Handicap::None {
+            return false;
+        }
+        let (human, engine) = match (record.red, record.yellow) {
+            (PlayerKind::Human, PlayerKind::Engine(engine)) => (Player::Red, engine),
+            (PlayerKind::Engine(engine), PlayerKind::Human) => (Player::Yellow, engine),
+            _ => return false,
+        };
+        let outcome = match record.result {
+            GameResult::InProgress => return false,
+            GameResult::Draw => Outcome::Draw,
+            GameResult::Win(winner) | GameResult::WinOnTime(winner) if winner == human => {
+                Outcome::Win
+            }
+            GameResult::Win(_) | GameResult::WinOnTime(_) => Outcome::Loss,
+        };
+        let plies = record.moves.len();
+
+        let stats = self.profiles.entry(profile.to_string()).or_default();
+        stats.overall.add(outcome, plies);
+        // Red is the first player, even in games started from an opening
+        if human == Player::Red {
+            stats.moving_first.add(outcome, plies);
+        } else {
+            stats.moving_second.add(outcome, plies);
+        }
+        let clock = record.clock.as_ref().map(|clock| clock.control());
+        stats
+            .by_engine
+            .entry(engine_label(&engine, clock))
+            .or_default()
+            .add(outcome, plies);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Board, GameClock};
+
+    fn engine(depth: u8) -> PlayerKind {
+        PlayerKind::Engine(EngineSettings {
+            depth,
+            ..EngineSettings::default()
+        })
+    }
+
+    fn finished(
+        red: PlayerKind,
+        yellow: PlayerKind,
+        result: GameResult,
+        plies: usize,
+    ) -> GameRecord {
+        let mut record = GameRecord::new(red, yellow);
+        record.moves = vec![3; plies];
+        record.result = result;
+        record
+    }
+
+    #[test]
+    fn counts_results_from_the_humans_side() {
+        let mut stats = Statistics::default();
+        let games = [
+            finished(
+                PlayerKind::Human,
+                engine(9),
+                GameResult::Win(Player::Red),
+                7,
+            ),
+            finished(
+                engine(9),
+                PlayerKind::Human,
+                GameResult::Win(Player::Red),
+                10,
+            ),
+            finished(PlayerKind::Human, engine(5), GameResult::Draw, 42),
+            finished(
+                engine(5),
+                PlayerKind::Human,
+                GameResult::WinOnTime(Player::Yellow),
+                11,
+            ),
+        ];
+        for game in &games {
+            assert!(stats.record_game("me", game));
+        }
+
+        let me = stats.profile("me").unwrap();
+        assert_eq!(
+            (me.overall.wins, me.overall.losses, me.overall.draws),
+            (2, 1, 1)
+        );
+        assert_eq!(me.overall.average_plies(), Some(70.0 / 4.0));
+        assert_eq!(me.overall.win_rate(), Some(0.5));
+        assert_eq!(me.moving_first.games(), 2);
+        assert_eq!(me.moving_second.wins, 1);
+        assert_eq!(me.moving_second.losses, 1);
+        assert_eq!(me.by_engine["depth 9"].games(), 2);
+        assert_eq!(me.by_engine["depth 5"].draws, 1);
+        assert!(stats.profile("someone else").is_none());
+    }
+
+    #[test]
+    fn moving_first_means_playing_red() {
+        let mut stats = Statistics::default();
+        let mut game = finished(
+            engine(9),
+            PlayerKind::Human,
+            GameResult::Win(Player::Yellow),
+            5,
+        );
+        // Yellow has the first move after this opening, but is still the second player
+        game.start_position = Some(Board::from_moves("4").unwrap());
+        assert!(stats.record_game("me", &game));
+        let profile = stats.profile("me").unwrap();
+        assert_eq!(profile.moving_first, Tally::default());
+        assert_eq!(profile.moving_second.wins, 1);
+    }
+
+    #[test]
+    fn skips_games_that_dont_count() {
+        let mut stats = Statistics::default();
+        let unfinished = finished(PlayerKind::Human, engine(9), GameResult::InProgress, 3);
+        let two_humans = finished(PlayerKind::Human, PlayerKind::Human, GameResult::Draw, 42);
+        let two_engines = finished(engine(9), engine(9), GameResult::Draw, 42);
+        let mut handicap = finished(
+            PlayerKind::Human,
+            engine(9),
+            GameResult::Win(Player::Red),
+            9,
+        );
+        handicap.handicap = Handicap::ExtraMove;
+        for game in [unfinished, two_humans, two_engines, handicap] {
+            assert!(!stats.record_game("me", &game));
+        }
+        assert_eq!(stats, Statistics::default());
+    }
+
+    #[test]
+    fn time_limited_and_timed_games_get_their_own_buckets() {
+        let mut stats = Statistics::default();
+        let limited = PlayerKind::Engine(EngineSettings {
+            depth: 9,
+            time_limit_ms: Some(500),
+            threads: 4,
+        });
+        let control = TimeControl {
+            initial_ms: 180_000,
+            increment_ms: 2000,
+        };
+        let mut timed = finished(PlayerKind::Human, engine(9), GameResult::Draw, 42);
+        timed.clock = Some(GameClock::new(control));
+        let games = [
+            finished(PlayerKind::Human, engine(9), GameResult::Draw, 42),
+            finished(PlayerKind::Human, limited, GameResult::Draw, 42),
+            timed,
+        ];
+        for game in &games {
+            assert!(stats.record_game("me", game));
+        }
+        let labels: Vec<&str> = stats
+            .profile("me")
+            .unwrap()
+            .by_engine
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                "depth 9",
+                "depth 9, 3+2 clock",
+                "up to depth 9, 500 ms per move, 4 threads"
+            ]
+        );
+        // Threads don't change a fixed-depth search
+        let threaded = EngineSettings {
+            threads: 4,
+            ..EngineSettings::default()
+        };
+        assert_eq!(engine_label(&threaded, None), "depth 9");
+    }
+}